@@ -0,0 +1,11 @@
+import { defineConfig } from '@playwright/test';
+
+// Main-process logic tested directly in Node; no app or browser needed
+export default defineConfig({
+  testDir: './tests/unit',
+  fullyParallel: true,
+  forbidOnly: !!process.env.CI,
+  retries: 0,
+  reporter: 'list',
+  timeout: 10_000,
+});
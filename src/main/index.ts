@@ -1,4 +1,4 @@
-import { app, BrowserWindow, nativeImage, nativeTheme, shell } from 'electron';
+import { app, BrowserWindow, dialog, nativeImage, nativeTheme, shell } from 'electron';
 import { join } from 'path';
 import { createIPCHandler } from 'trpc-electron/main';
 import { appRouter } from './trpc/router';
@@ -35,6 +35,8 @@ if (process.env.MULTIPLEXER_E2E === '1') {
 let mainWindow: BrowserWindow | null = null;
 let services: AppServices | null = null;
 let cleanupAutoUpdater: (() => void) | null = null;
+let shutdownStarted = false;
+let shutdownComplete = false;
 
 function createWindow(): BrowserWindow {
   const win = new BrowserWindow({
@@ -86,7 +88,15 @@ app.whenReady().then(() => {
   });
   createApplicationMenu();
 
-  services = AppServices.initialize();
+  try {
+    services = AppServices.initialize();
+  } catch (e) {
+    // e.g. workspace locked by another instance, or state from a newer version
+    const msg = e instanceof Error ? e.message : String(e);
+    dialog.showErrorBox('Multiplexer could not start', msg);
+    app.quit();
+    return;
+  }
   cleanupAutoUpdater = setupAutoUpdater();
   mainWindow = createWindow();
 
@@ -116,7 +126,18 @@ app.on('window-all-closed', () => {
   }
 });
 
-app.on('before-quit', () => {
+// Hold the quit until in-flight polls drain and state is flushed, then quit for real
+app.on('before-quit', (event) => {
+  if (shutdownComplete || !services) return;
+  event.preventDefault();
+  if (shutdownStarted) return;
+  shutdownStarted = true;
   cleanupAutoUpdater?.();
-  services?.shutdown();
+  services
+    .shutdown()
+    .catch((err) => console.error('Shutdown error:', err))
+    .finally(() => {
+      shutdownComplete = true;
+      app.quit();
+    });
 });
@@ -0,0 +1,205 @@
+// ── SMILES Ring Analysis ────────────────────────────────────────────
+//
+// Just enough SMILES parsing to find rings and name common cores. This is
+// a heuristic for suggestions, not a chemistry toolkit: stereo, charges,
+// and hydrogens are ignored, and unrecognised rings get generic names.
+
+interface Atom {
+  element: string; // capitalised, e.g. "C", "N", "Cl"
+  aromatic: boolean;
+}
+
+interface MolGraph {
+  atoms: Atom[];
+  neighbours: number[][];
+  closures: [number, number][]; // bonds created by ring-closure digits
+}
+
+export interface Ring {
+  atoms: number[]; // atom indices in ring order
+  aromatic: boolean;
+}
+
+export interface RingSystem {
+  name: string; // e.g. "benzimidazole", or "fused 6-5 aromatic system"
+  rings: Ring[];
+}
+
+const ATOM_RE = /^(\[[^\]]+\]|Cl|Br|[BCNOSPFI]|[bcnosp])/;
+
+/** Parse SMILES into an atom graph. Throws on unbalanced branches or ring labels. */
+function parseSmilesGraph(smiles: string): MolGraph {
+  const atoms: Atom[] = [];
+  const neighbours: number[][] = [];
+  const closures: [number, number][] = [];
+  const openRings = new Map<string, number>();
+  const branchStack: number[] = [];
+  let prev: number | null = null;
+
+  const bond = (a: number, b: number) => {
+    neighbours[a].push(b);
+    neighbours[b].push(a);
+  };
+
+  let i = 0;
+  while (i < smiles.length) {
+    const ch = smiles[i];
+    const atomMatch = ATOM_RE.exec(smiles.slice(i));
+    if (atomMatch) {
+      const token = atomMatch[1];
+      const symbol = token.startsWith('[')
+        ? (/^\[\d*([A-Za-z][a-z]?)/.exec(token)?.[1] ?? 'C')
+        : token;
+      const aromatic = symbol[0] === symbol[0].toLowerCase();
+      atoms.push({ element: symbol[0].toUpperCase() + symbol.slice(1), aromatic });
+      neighbours.push([]);
+      const index = atoms.length - 1;
+      if (prev !== null) bond(prev, index);
+      prev = index;
+      i += token.length;
+      continue;
+    }
+
+    if (ch === '(') {
+      if (prev === null) throw new Error('Branch before any atom');
+      branchStack.push(prev);
+    } else if (ch === ')') {
+      const top = branchStack.pop();
+      if (top === undefined) throw new Error('Unbalanced ")"');
+      prev = top;
+    } else if (ch === '.') {
+      prev = null;
+    } else if (/[0-9%]/.test(ch)) {
+      const label = ch === '%' ? smiles.slice(i + 1, i + 3) : ch;
+      if (prev === null) throw new Error('Ring label before any atom');
+      const open = openRings.get(label);
+      if (open === undefined) {
+        openRings.set(label, prev);
+      } else {
+        bond(open, prev);
+        closures.push([open, prev]);
+        openRings.delete(label);
+      }
+      i += ch === '%' ? 3 : 1;
+      continue;
+    }
+    // Bond symbols (- = # : / \) and anything else carry no ring information
+    i++;
+  }
+
+  if (branchStack.length > 0) throw new Error('Unbalanced "("');
+  if (openRings.size > 0) throw new Error(`Unclosed ring label ${[...openRings.keys()][0]}`);
+  return { atoms, neighbours, closures };
+}
+
+/** Shortest path from a to b that doesn't use the direct a–b bond */
+function ringPath(graph: MolGraph, a: number, b: number): number[] {
+  const previous = new Map<number, number>([[a, -1]]);
+  const queue = [a];
+  while (queue.length > 0) {
+    const cur = queue.shift()!;
+    for (const next of graph.neighbours[cur]) {
+      if (cur === a && next === b) continue;
+      if (previous.has(next)) continue;
+      previous.set(next, cur);
+      if (next === b) {
+        const path = [b];
+        for (let p = cur; p !== -1; p = previous.get(p)!) path.push(p);
+        return path.reverse();
+      }
+      queue.push(next);
+    }
+  }
+  return [a, b];
+}
+
+/** Name a single ring from its size, aromaticity, and heteroatoms */
+function ringName(graph: MolGraph, ring: Ring): string {
+  const elements = ring.atoms.map((i) => graph.atoms[i].element);
+  const hetero = elements.filter((e) => e !== 'C').sort().join('');
+  const size = ring.atoms.length;
+  // Heteroatoms next to each other distinguish pyrazole from imidazole, etc.
+  const adjacentHetero = elements.some(
+    (e, i) => e !== 'C' && elements[(i + 1) % size] !== 'C',
+  );
+
+  if (ring.aromatic && size === 6) {
+    return { '': 'benzene', N: 'pyridine', NN: adjacentHetero ? 'pyridazine' : 'pyrimidine' }[hetero] ??
+      '6-membered heteroaromatic ring';
+  }
+  if (ring.aromatic && size === 5) {
+    const names: Record<string, string> = {
+      S: 'thiophene',
+      O: 'furan',
+      N: 'pyrrole',
+      NN: adjacentHetero ? 'pyrazole' : 'imidazole',
+      NS: adjacentHetero ? 'isothiazole' : 'thiazole',
+      NO: adjacentHetero ? 'isoxazole' : 'oxazole',
+      NNN: 'triazole',
+      NNNN: 'tetrazole',
+    };
+    return names[hetero] ?? '5-membered heteroaromatic ring';
+  }
+  if (!ring.aromatic) {
+    const names: Record<string, string> = {
+      '3:': 'cyclopropane',
+      '4:': 'cyclobutane',
+      '5:': 'cyclopentane',
+      '6:': 'cyclohexane',
+      '5:N': 'pyrrolidine',
+      '6:N': 'piperidine',
+      '6:NN': 'piperazine',
+      '6:NO': 'morpholine',
+      '6:O': 'tetrahydropyran',
+      '5:O': 'tetrahydrofuran',
+    };
+    return names[`${size}:${hetero}`] ?? `${size}-membered ring`;
+  }
+  return `${size}-membered aromatic ring`;
+}
+
+const FUSED_NAMES: Record<string, string> = {
+  'benzene+benzene': 'naphthalene',
+  'benzene+pyridine': 'quinoline',
+  'benzene+pyrrole': 'indole',
+  'benzene+imidazole': 'benzimidazole',
+  'benzene+pyrazole': 'indazole',
+  'benzene+thiophene': 'benzothiophene',
+  'benzene+furan': 'benzofuran',
+  'benzene+thiazole': 'benzothiazole',
+  'benzene+oxazole': 'benzoxazole',
+  'benzene+pyrimidine': 'quinazoline',
+  'imidazole+pyrimidine': 'purine',
+};
+
+/**
+ * Find the ring systems (rings sharing atoms are fused into one system) in
+ * a SMILES string, each with a common name where one is known.
+ */
+export function findRingSystems(smiles: string): RingSystem[] {
+  const graph = parseSmilesGraph(smiles);
+  const rings: Ring[] = graph.closures.map(([a, b]) => {
+    const atoms = ringPath(graph, a, b);
+    return { atoms, aromatic: atoms.every((i) => graph.atoms[i].aromatic) };
+  });
+
+  // Union rings that share any atom
+  const systems: Ring[][] = [];
+  for (const ring of rings) {
+    const members = new Set(ring.atoms);
+    const touching = systems.filter((s) => s.some((r) => r.atoms.some((i) => members.has(i))));
+    const merged = [ring, ...touching.flat()];
+    for (const s of touching) systems.splice(systems.indexOf(s), 1);
+    systems.push(merged);
+  }
+
+  return systems.map((system) => {
+    const names = system.map((r) => ringName(graph, r)).sort();
+    if (names.length === 1) return { name: names[0], rings: system };
+    const fused = FUSED_NAMES[names.join('+')];
+    if (fused) return { name: fused, rings: system };
+    const sizes = system.map((r) => r.atoms.length).sort((a, b) => b - a).join('-');
+    const aromatic = system.every((r) => r.aromatic) ? ' aromatic' : '';
+    return { name: `fused ${sizes}${aromatic} system`, rings: system };
+  });
+}
@@ -0,0 +1,64 @@
+// ── mmCIF Atom Sites ────────────────────────────────────────────────
+
+export interface CifAtom {
+  element: string; // type_symbol, e.g. "C", "CL"
+  chain: string; // auth_asym_id, falling back to label_asym_id
+  x: number;
+  y: number;
+  z: number;
+}
+
+/** Split a CIF data line into values, honouring '…' and "…" quoting. */
+function tokenize(line: string): string[] {
+  const tokens: string[] = [];
+  const re = /'([^']*)'(?=\s|$)|"([^"]*)"(?=\s|$)|(\S+)/g;
+  let m: RegExpExecArray | null;
+  while ((m = re.exec(line)) !== null) {
+    tokens.push(m[1] ?? m[2] ?? m[3]);
+  }
+  return tokens;
+}
+
+/**
+ * Read the atom_site loop of a PDBx/mmCIF file. Only the basic layout
+ * written by Boltz is handled: one row per line, no multi-line (;) values
+ * inside the loop. Hydrogens are kept; the caller can filter them.
+ */
+export function parseCifAtoms(text: string): CifAtom[] {
+  const lines = text.split(/\r?\n/);
+  const atoms: CifAtom[] = [];
+
+  for (let i = 0; i < lines.length; i++) {
+    if (lines[i].trim() !== 'loop_' || !lines[i + 1]?.trim().startsWith('_atom_site.')) continue;
+
+    const columns: string[] = [];
+    let j = i + 1;
+    for (; j < lines.length && lines[j].trim().startsWith('_atom_site.'); j++) {
+      columns.push(lines[j].trim().slice('_atom_site.'.length));
+    }
+    const col = (name: string) => columns.indexOf(name);
+    const element = col('type_symbol');
+    const chain = col('auth_asym_id') !== -1 ? col('auth_asym_id') : col('label_asym_id');
+    const [x, y, z] = [col('Cartn_x'), col('Cartn_y'), col('Cartn_z')];
+    if ([element, chain, x, y, z].includes(-1)) {
+      throw new Error('atom_site loop is missing element, chain, or coordinate columns');
+    }
+
+    for (; j < lines.length; j++) {
+      const line = lines[j].trim();
+      if (!line || line === '#' || line.startsWith('_') || line === 'loop_') break;
+      const values = tokenize(line);
+      if (values.length < columns.length) continue;
+      atoms.push({
+        element: values[element],
+        chain: values[chain],
+        x: Number(values[x]),
+        y: Number(values[y]),
+        z: Number(values[z]),
+      });
+    }
+    break;
+  }
+
+  return atoms;
+}
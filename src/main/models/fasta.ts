@@ -0,0 +1,41 @@
+// ── FASTA Parsing ───────────────────────────────────────────────────
+
+export interface FastaEntry {
+  header: string; // text after '>', trimmed
+  sequence: string; // sequence lines concatenated, whitespace removed, uppercased
+}
+
+/**
+ * Parse FASTA text into entries. Multi-line sequences are concatenated and
+ * both \n and \r\n line endings are accepted. Blank lines and ';' comment
+ * lines are ignored. Throws if sequence data appears before the first header
+ * or an entry has no sequence.
+ */
+export function parseFasta(text: string): FastaEntry[] {
+  const entries: FastaEntry[] = [];
+  let current: { header: string; lines: string[] } | null = null;
+
+  const finish = () => {
+    if (!current) return;
+    const sequence = current.lines.join('').replace(/\s+/g, '').toUpperCase();
+    if (!sequence) throw new Error(`FASTA entry "${current.header}" has no sequence`);
+    entries.push({ header: current.header, sequence });
+  };
+
+  const lines = text.replace(/^\uFEFF/, '').split(/\r?\n/);
+  lines.forEach((raw, i) => {
+    const line = raw.trim();
+    if (!line || line.startsWith(';')) return;
+    if (line.startsWith('>')) {
+      finish();
+      current = { header: line.slice(1).trim(), lines: [] };
+      if (!current.header) throw new Error(`Line ${i + 1}: FASTA header is empty`);
+      return;
+    }
+    if (!current) throw new Error(`Line ${i + 1}: sequence data before the first '>' header`);
+    current.lines.push(line);
+  });
+  finish();
+
+  return entries;
+}
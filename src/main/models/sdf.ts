@@ -0,0 +1,107 @@
+// ── SDF Parsing ─────────────────────────────────────────────────────
+
+export interface SdfRecord {
+  index: number; // 1-based position in the file
+  name: string; // first line of the molblock, trimmed
+  smiles: string | null; // from a "> <SMILES>" data field, if present
+}
+
+/**
+ * Split an SD file into records and pull out each molecule's name and
+ * SMILES data field (matched case-insensitively). Molblocks are not
+ * converted to SMILES — records without the field come back with
+ * smiles: null for the caller to skip.
+ */
+export function parseSdf(text: string): SdfRecord[] {
+  const records: SdfRecord[] = [];
+  const blocks = text.replace(/^\uFEFF/, '').split(/^\$\$\$\$[^\S\r\n]*\r?$/m);
+
+  for (const block of blocks) {
+    const lines = block.replace(/^\r?\n/, '').split(/\r?\n/);
+    if (lines.every((l) => l.trim() === '')) continue; // trailing text after the last $$$$
+
+    let smiles: string | null = null;
+    for (let i = 0; i < lines.length; i++) {
+      const header = lines[i].match(/^>.*<([^>]+)>/);
+      if (header && header[1].trim().toUpperCase() === 'SMILES') {
+        smiles = (lines[i + 1] ?? '').trim() || null;
+        break;
+      }
+    }
+
+    records.push({ index: records.length + 1, name: lines[0].trim(), smiles });
+  }
+
+  return records;
+}
+
+// ── SDF Writing ─────────────────────────────────────────────────────
+
+/** Covalent radii (Å) for bond perception; anything else uses DEFAULT_RADIUS */
+const COVALENT_RADII: Record<string, number> = {
+  H: 0.31, B: 0.84, C: 0.76, N: 0.71, O: 0.66, F: 0.57, P: 1.07, S: 1.05, CL: 1.02, BR: 1.2, I: 1.39,
+};
+const DEFAULT_RADIUS = 0.8;
+const BOND_TOLERANCE = 0.45;
+
+export interface SdfAtom {
+  element: string;
+  x: number;
+  y: number;
+  z: number;
+}
+
+/** "CL" → "Cl", as MOL files expect */
+function elementSymbol(element: string): string {
+  return element.charAt(0).toUpperCase() + element.slice(1).toLowerCase();
+}
+
+/**
+ * A minimal V2000 molblock for a set of atoms. Bonds are guessed from
+ * interatomic distances and written as single bonds — bond orders and
+ * charges are not recovered from coordinates.
+ */
+export function buildMolblock(name: string, atoms: SdfAtom[]): string {
+  const bonds: [number, number][] = [];
+  for (let i = 0; i < atoms.length; i++) {
+    for (let j = i + 1; j < atoms.length; j++) {
+      const a = atoms[i];
+      const b = atoms[j];
+      const maxDist =
+        (COVALENT_RADII[a.element.toUpperCase()] ?? DEFAULT_RADIUS) +
+        (COVALENT_RADII[b.element.toUpperCase()] ?? DEFAULT_RADIUS) +
+        BOND_TOLERANCE;
+      const dist = Math.hypot(a.x - b.x, a.y - b.y, a.z - b.z);
+      if (dist > 0.4 && dist <= maxDist) bonds.push([i + 1, j + 1]);
+    }
+  }
+
+  const pad = (v: string | number, width: number) => String(v).padStart(width);
+  const lines = [
+    name.replace(/[\r\n]+/g, ' ').slice(0, 80),
+    '  Multiplexer',
+    '',
+    `${pad(atoms.length, 3)}${pad(bonds.length, 3)}  0  0  0  0  0  0  0  0999 V2000`,
+    ...atoms.map(
+      (a) =>
+        `${pad(a.x.toFixed(4), 10)}${pad(a.y.toFixed(4), 10)}${pad(a.z.toFixed(4), 10)} ` +
+        `${elementSymbol(a.element).padEnd(3)} 0  0  0  0  0  0  0  0  0  0  0  0`,
+    ),
+    ...bonds.map(([i, j]) => `${pad(i, 3)}${pad(j, 3)}  1  0`),
+    'M  END',
+  ];
+  return lines.join('\n');
+}
+
+/**
+ * Join molblocks into one SD file; each record's name is its first line.
+ * A SMILES data field is added when given, so parseSdf can read it back.
+ */
+export function writeSdf(records: { name: string; atoms: SdfAtom[]; smiles?: string }[]): string {
+  return records
+    .map((r) => {
+      const data = r.smiles ? `> <SMILES>\n${r.smiles}\n\n` : '';
+      return `${buildMolblock(r.name, r.atoms)}\n${data}$$$$\n`;
+    })
+    .join('');
+}
@@ -1,17 +1,182 @@
+import { createHash } from 'node:crypto';
+import { v4 as uuidv4 } from 'uuid';
 import type {
+  ApiKeyEntry,
   AppData,
+  AuditEntry,
   Campaign,
   Run,
   Compound,
+  CompoundInput,
   CompoundRef,
+  CompoundSearchResult,
+  CompoundSearchScope,
+  DuplicateSmilesPair,
+  GcSummary,
   RunCompletedEvent,
+  QuotaInfo,
+  RunParams,
+  RunTemplate,
+  SmilesEntry,
+  TargetType,
 } from './types';
-import { isTerminal } from './types';
+import {
+  CURRENT_SCHEMA_VERSION,
+  DEFAULT_LIGAND_CHAIN,
+  MAX_AUDIT_ENTRIES,
+  isTerminal,
+} from './types';
+
+/**
+ * Comparison key for SMILES. RDKit isn't available in the main process, so
+ * this is an exact match after trimming — it won't catch different
+ * spellings of the same molecule.
+ */
+function smilesKey(smiles: string): string {
+  return smiles.trim();
+}
+
+/**
+ * Pair every input whose SMILES repeats an existing compound or an
+ * earlier input in the same list.
+ */
+export function findDuplicateSmiles(
+  existing: { display_name: string; smiles: string }[],
+  inputs: CompoundInput[],
+): DuplicateSmilesPair[] {
+  const seen = new Map<string, string>();
+  for (const c of existing) {
+    const key = smilesKey(c.smiles);
+    if (!seen.has(key)) seen.set(key, c.display_name);
+  }
+
+  const pairs: DuplicateSmilesPair[] = [];
+  for (const input of inputs) {
+    const key = smilesKey(input.smiles);
+    const first = seen.get(key);
+    if (first !== undefined) {
+      pairs.push({ smiles: key, first, second: input.name });
+    } else {
+      seen.set(key, input.name);
+    }
+  }
+  return pairs;
+}
+
+/** display_order for an item appended after every existing one */
+export function nextDisplayOrder(items: { display_order: number }[]): number {
+  return items.reduce((max, item) => Math.max(max, item.display_order + 1), 0);
+}
+
+/** Sort by display_order, then created_at (oldest first) */
+export function byDisplayOrder(
+  a: { display_order: number; created_at: string },
+  b: { display_order: number; created_at: string },
+): number {
+  return a.display_order - b.display_order || a.created_at.localeCompare(b.created_at);
+}
+
+/** A single ligand on the default chain */
+export function singleLigand(smiles: string): SmilesEntry[] {
+  return [{ smiles, chain_id: DEFAULT_LIGAND_CHAIN }];
+}
+
+/**
+ * Deduplication key for a prediction: SHA-256 over the normalised target,
+ * ligands, and Boltz parameters. Equal hashes mean an identical API request.
+ * A single default-chain ligand hashes as a bare SMILES, so hashes from
+ * before multi-ligand support still match.
+ */
+export function computeInputHash(
+  targetType: TargetType,
+  targetSequence: string,
+  ligands: SmilesEntry[],
+  params: RunParams,
+): string {
+  const ligandKey =
+    ligands.length === 1 && ligands[0].chain_id === DEFAULT_LIGAND_CHAIN
+      ? smilesKey(ligands[0].smiles)
+      : ligands.map((l) => [l.chain_id, smilesKey(l.smiles)]);
+  const normalised = JSON.stringify([
+    targetType,
+    targetSequence.trim().toUpperCase(),
+    ligandKey,
+    params.recycling_steps,
+    params.diffusion_samples,
+    params.sampling_steps,
+    params.step_scale,
+  ]);
+  return createHash('sha256').update(normalised).digest('hex');
+}
+
+/**
+ * Structural sanity checks on the whole tree: duplicate campaign/run/compound
+ * IDs and folder names containing path separators. Returns one message per
+ * problem; an empty list means the data looks consistent.
+ */
+export function checkIntegrity(data: AppData): string[] {
+  const warnings: string[] = [];
+  const seen = { campaign: new Set<string>(), run: new Set<string>(), compound: new Set<string>() };
+
+  const check = (kind: keyof typeof seen, id: string, folderName: string) => {
+    if (seen[kind].has(id)) warnings.push(`Duplicate ${kind} ID: ${id}`);
+    seen[kind].add(id);
+    if (/[/\\]/.test(folderName)) {
+      warnings.push(`${kind} ${id} has a path separator in its folder name: "${folderName}"`);
+    }
+  };
+
+  for (const campaign of data.campaigns) {
+    check('campaign', campaign.id, campaign.folder_name);
+    for (const run of campaign.runs) {
+      check('run', run.id, run.folder_name);
+      for (const compound of run.compounds) {
+        check('compound', compound.id, compound.folder_name);
+      }
+    }
+  }
+  return warnings;
+}
+
+// ── Folder Lookups ──────────────────────────────────────────────────
+//
+// Map a path on disk back to its state entry, e.g. during recovery or
+// folder scans. These are linear scans; index by folder name if
+// workspaces start reaching 1000+ campaigns.
+
+export function findCampaignByFolder(data: AppData, folderName: string): Campaign | undefined {
+  return data.campaigns.find((c) => c.folder_name === folderName);
+}
+
+/** Returns [campaign, run] for rootDir/campaignFolder/runFolder, or undefined */
+export function findRunByFolder(
+  data: AppData,
+  campaignFolder: string,
+  runFolder: string,
+): [Campaign, Run] | undefined {
+  const campaign = findCampaignByFolder(data, campaignFolder);
+  const run = campaign?.runs.find((r) => r.folder_name === runFolder);
+  return campaign && run ? [campaign, run] : undefined;
+}
+
+/** Returns [campaign, run, compound] for a compound folder path, or undefined */
+export function findCompoundByFolder(
+  data: AppData,
+  campaignFolder: string,
+  runFolder: string,
+  compoundFolder: string,
+): [Campaign, Run, Compound] | undefined {
+  const context = findRunByFolder(data, campaignFolder, runFolder);
+  const compound = context?.[1].compounds.find((c) => c.folder_name === compoundFolder);
+  return context && compound ? [...context, compound] : undefined;
+}
 
 export class AppState {
   data: AppData;
   dirty: boolean;
   rootDir: string;
+  /** Last quota response; in memory only, refreshed by settings.checkQuota */
+  quota: QuotaInfo | null = null;
 
   constructor(data: AppData, rootDir: string) {
     this.data = data;
@@ -21,9 +186,12 @@ export class AppState {
 
   static defaultData(): AppData {
     return {
-      schema_version: 3,
-      api_key: null,
+      schema_version: CURRENT_SCHEMA_VERSION,
+      api_keys: [],
+      active_api_key_id: null,
       campaigns: [],
+      templates: [],
+      audit_log: [],
     };
   }
 
@@ -31,6 +199,44 @@ export class AppState {
     this.dirty = true;
   }
 
+  // ── API Keys ──────────────────────────────────────────────────────
+
+  /** The active entry, or the default one when none is selected */
+  activeApiKeyEntry(): ApiKeyEntry | undefined {
+    const keys = this.data.api_keys;
+    return (
+      keys.find((k) => k.id === this.data.active_api_key_id) ?? keys.find((k) => k.is_default)
+    );
+  }
+
+  /** Key to send with API requests, or null when none is configured */
+  activeApiKey(): string | null {
+    return this.activeApiKeyEntry()?.key ?? null;
+  }
+
+  /**
+   * Append an audit entry for a state-mutating operation, dropping the
+   * oldest entries once the log exceeds MAX_AUDIT_ENTRIES.
+   */
+  audit(
+    operation: string,
+    entityType: AuditEntry['entity_type'],
+    entityId: string,
+    details = '',
+  ): void {
+    this.data.audit_log.push({
+      id: uuidv4(),
+      timestamp: new Date().toISOString(),
+      operation,
+      entity_type: entityType,
+      entity_id: entityId,
+      details,
+    });
+    const excess = this.data.audit_log.length - MAX_AUDIT_ENTRIES;
+    if (excess > 0) this.data.audit_log.splice(0, excess);
+    this.markDirty();
+  }
+
   // ── Finders ───────────────────────────────────────────────────────
 
   findCampaign(campaignId: string): Campaign | undefined {
@@ -45,6 +251,10 @@ export class AppState {
     return undefined;
   }
 
+  findTemplate(templateId: string): RunTemplate | undefined {
+    return this.data.templates.find((t) => t.id === templateId);
+  }
+
   findCompound(compoundId: string): Compound | undefined {
     for (const campaign of this.data.campaigns) {
       for (const run of campaign.runs) {
@@ -68,11 +278,95 @@ export class AppState {
     return undefined;
   }
 
+  /** Returns [campaign, run] or undefined */
+  findRunContext(runId: string): [Campaign, Run] | undefined {
+    for (const campaign of this.data.campaigns) {
+      const run = campaign.runs.find((r) => r.id === runId);
+      if (run) return [campaign, run];
+    }
+    return undefined;
+  }
+
+  /** Flatten the tree into [campaign, run, compound] triples */
+  allCompoundContexts(): [Campaign, Run, Compound][] {
+    const result: [Campaign, Run, Compound][] = [];
+    for (const campaign of this.data.campaigns) {
+      for (const run of campaign.runs) {
+        for (const compound of run.compounds) {
+          result.push([campaign, run, compound]);
+        }
+      }
+    }
+    return result;
+  }
+
+  /** Compounds whose outputs failed to download, as [campaign, run, compound] */
+  scanDownloadErrors(): [Campaign, Run, Compound][] {
+    return this.allCompoundContexts().filter(([, , compound]) => compound.download_error !== null);
+  }
+
   /** Find the campaign that contains a given run */
   findCampaignForRun(runId: string): Campaign | undefined {
     return this.data.campaigns.find((c) => c.runs.some((r) => r.id === runId));
   }
 
+  // ── Search ────────────────────────────────────────────────────────
+
+  /**
+   * Case-insensitive substring match on display name and SMILES, optionally
+   * limited to one campaign or run. Most recently submitted first.
+   */
+  searchCompounds(query: string, scope: CompoundSearchScope = {}): CompoundSearchResult[] {
+    const needle = query.trim().toLowerCase();
+    const { campaign_id, run_id } = scope;
+
+    const results: CompoundSearchResult[] = [];
+    for (const [campaign, run, compound] of this.allCompoundContexts()) {
+      if (campaign_id && campaign.id !== campaign_id) continue;
+      if (run_id && run.id !== run_id) continue;
+      if (
+        !compound.display_name.toLowerCase().includes(needle) &&
+        !compound.smiles.toLowerCase().includes(needle)
+      ) {
+        continue;
+      }
+      results.push({
+        ...compound,
+        campaign_id: campaign.id,
+        campaign_name: campaign.display_name,
+        run_id: run.id,
+        run_name: run.display_name,
+      });
+    }
+
+    // Never-submitted compounds last
+    results.sort((a, b) => (b.submitted_at ?? '').localeCompare(a.submitted_at ?? ''));
+    return results;
+  }
+
+  // ── Duplicate Detection ───────────────────────────────────────────
+
+  /** A COMPLETED compound whose prediction had exactly these inputs */
+  findMatchingPrediction(inputHash: string): [Campaign, Run, Compound] | undefined {
+    return this.allCompoundContexts().find(
+      ([, , c]) => c.status === 'COMPLETED' && c.input_hash === inputHash,
+    );
+  }
+
+  findDuplicateSmilesInRun(runId: string, inputs: CompoundInput[]): DuplicateSmilesPair[] {
+    const run = this.findRun(runId);
+    return findDuplicateSmiles(run?.compounds ?? [], inputs);
+  }
+
+  findDuplicateSmilesInCampaign(
+    campaignId: string,
+    inputs: CompoundInput[],
+  ): DuplicateSmilesPair[] {
+    const campaign = this.findCampaign(campaignId);
+    const existing = campaign?.runs.flatMap((r) => r.compounds) ?? [];
+    return findDuplicateSmiles(existing, inputs);
+  }
+
   // ── Poller Helpers ────────────────────────────────────────────────
 
   /** Collect all in-progress compounds for the poller */
@@ -92,6 +386,7 @@ export class AppState {
               campaign_id: campaign.id,
               run_id: run.id,
               submitted_at: compound.submitted_at,
+              run_timeout_secs: run.params.timeout_secs,
             });
           }
         }
@@ -100,28 +395,88 @@ export class AppState {
     return refs;
   }
 
+  /**
+   * Drop compounds from runs archived (directly or via their campaign)
+   * longer than olderThanMs ago, keeping per-status counts on the run.
+   * Output folders on disk are left alone. Runs with compounds still in
+   * progress are skipped.
+   */
+  gcArchivedData(olderThanMs: number): GcSummary {
+    const cutoff = Date.now() - olderThanMs;
+    const summary: GcSummary = { campaigns_compacted: 0, runs_compacted: 0, compounds_removed: 0 };
+    const nowIso = new Date().toISOString();
+
+    for (const campaign of this.data.campaigns) {
+      let compacted = false;
+      for (const run of campaign.runs) {
+        const archivedAt = run.archived
+          ? run.archived_at
+          : campaign.archived
+            ? campaign.archived_at
+            : null;
+        if (!archivedAt || Date.parse(archivedAt) > cutoff) continue;
+        if (run.compounds.length === 0) continue;
+        if (run.compounds.some((c) => !isTerminal(c.status))) continue;
+
+        const byStatus = { ...run.compacted?.compounds_by_status };
+        for (const c of run.compounds) {
+          byStatus[c.status] = (byStatus[c.status] ?? 0) + 1;
+        }
+        run.compacted = {
+          compacted_at: nowIso,
+          total_compounds: (run.compacted?.total_compounds ?? 0) + run.compounds.length,
+          compounds_by_status: byStatus,
+        };
+
+        this.audit('compact', 'run', run.id, `${run.compounds.length} compounds removed`);
+        summary.compounds_removed += run.compounds.length;
+        summary.runs_compacted++;
+        run.compounds = [];
+        compacted = true;
+      }
+      if (compacted) summary.campaigns_compacted++;
+    }
+
+    return summary;
+  }
+
   /**
    * Check if all compounds in a run are terminal.
    * Returns a RunCompletedEvent if the run just completed (completed_at not yet set).
    * Guards against duplicate events by checking completed_at.
    */
   checkRunCompletion(runId: string): RunCompletedEvent | null {
-    const campaign = this.findCampaignForRun(runId);
-    if (!campaign) return null;
-
-    const run = campaign.runs.find((r) => r.id === runId);
-    if (!run) return null;
+    const context = this.findRunContext(runId);
+    if (!context) return null;
+    const [campaign, run] = context;
 
     // Already marked as completed — don't emit again
     if (run.completed_at) return null;
 
-    const allTerminal = run.compounds.every((c) => isTerminal(c.status));
+    // Archived compounds are always terminal, but count them explicitly
+    const allTerminal = run.compounds.every((c) => c.archived || isTerminal(c.status));
     if (!allTerminal) return null;
 
     // Mark the run as completed
-    run.completed_at = new Date().toISOString();
+    const completedAt = new Date();
+    run.completed_at = completedAt.toISOString();
     this.dirty = true;
 
+    const createdMs = Date.parse(run.created_at);
+    const durationSecs = Number.isNaN(createdMs)
+      ? null
+      : Math.max(0, Math.round((completedAt.getTime() - createdMs) / 1000));
+
+    let fastest: number | null = null;
+    let slowest: number | null = null;
+    for (const c of run.compounds) {
+      if (!c.submitted_at || !c.completed_at) continue;
+      const secs = Math.round((Date.parse(c.completed_at) - Date.parse(c.submitted_at)) / 1000);
+      if (Number.isNaN(secs) || secs < 0) continue;
+      fastest = fastest === null ? secs : Math.min(fastest, secs);
+      slowest = slowest === null ? secs : Math.max(slowest, secs);
+    }
+
     return {
       run_id: run.id,
       campaign_id: campaign.id,
@@ -134,6 +489,9 @@ export class AppState {
         .length,
       cancelled_count: run.compounds.filter((c) => c.status === 'CANCELLED')
         .length,
+      duration_secs: durationSecs,
+      fastest_compound_secs: fastest,
+      slowest_compound_secs: slowest,
     };
   }
 }
@@ -0,0 +1,129 @@
+// ── Statistics ──────────────────────────────────────────────────────
+
+import type { Compound, Histogram, HistogramBucket, MetricField } from './types';
+
+/** Affinity metrics, or the sample-0 value for structure metrics */
+export function metricValue(compound: Compound, field: MetricField): number | null {
+  if (field === 'binding_confidence' || field === 'optimization_score') {
+    return compound.metrics?.affinity?.[field] ?? null;
+  }
+  return compound.metrics?.samples[0]?.[field] ?? null;
+}
+
+/**
+ * Evenly spaced buckets between min and max. No data gives no buckets;
+ * a single distinct value gives one zero-width bucket holding everything.
+ */
+export function buildHistogram(values: number[], bucketCount: number): Histogram {
+  const n = values.length;
+  if (n === 0) {
+    return { buckets: [], count: 0, min: null, max: null, mean: null, median: null };
+  }
+
+  const sorted = [...values].sort((a, b) => a - b);
+  const min = sorted[0];
+  const max = sorted[n - 1];
+  const mean = sorted.reduce((a, b) => a + b, 0) / n;
+  const median = n % 2 === 1 ? sorted[(n - 1) / 2] : (sorted[n / 2 - 1] + sorted[n / 2]) / 2;
+
+  let buckets: HistogramBucket[];
+  if (min === max) {
+    buckets = [{ lower: min, upper: max, count: n }];
+  } else {
+    const width = (max - min) / bucketCount;
+    buckets = Array.from({ length: bucketCount }, (_, i) => ({
+      lower: min + i * width,
+      upper: i === bucketCount - 1 ? max : min + (i + 1) * width,
+      count: 0,
+    }));
+    for (const v of sorted) {
+      buckets[Math.min(Math.floor((v - min) / width), bucketCount - 1)].count++;
+    }
+  }
+
+  return { buckets, count: n, min, max, mean, median };
+}
+
+/** ln Γ(x) via the Lanczos approximation (x > 0) */
+function logGamma(x: number): number {
+  const c = [
+    76.18009172947146, -86.50532032941677, 24.01409824083091, -1.231739572450155,
+    0.1208650973866179e-2, -0.5395239384953e-5,
+  ];
+  let y = x;
+  const tmp = x + 5.5 - (x + 0.5) * Math.log(x + 5.5);
+  let ser = 1.000000000190015;
+  for (const coef of c) ser += coef / ++y;
+  return -tmp + Math.log((2.5066282746310005 * ser) / x);
+}
+
+/** Continued fraction for the incomplete beta function (Lentz's method) */
+function betaContinuedFraction(a: number, b: number, x: number): number {
+  const TINY = 1e-30;
+  let c = 1;
+  let d = 1 - ((a + b) * x) / (a + 1);
+  if (Math.abs(d) < TINY) d = TINY;
+  d = 1 / d;
+  let h = d;
+  for (let m = 1; m <= 200; m++) {
+    const m2 = 2 * m;
+    let aa = (m * (b - m) * x) / ((a + m2 - 1) * (a + m2));
+    d = 1 + aa * d;
+    if (Math.abs(d) < TINY) d = TINY;
+    c = 1 + aa / c;
+    if (Math.abs(c) < TINY) c = TINY;
+    d = 1 / d;
+    h *= d * c;
+    aa = (-(a + m) * (a + b + m) * x) / ((a + m2) * (a + m2 + 1));
+    d = 1 + aa * d;
+    if (Math.abs(d) < TINY) d = TINY;
+    c = 1 + aa / c;
+    if (Math.abs(c) < TINY) c = TINY;
+    d = 1 / d;
+    const delta = d * c;
+    h *= delta;
+    if (Math.abs(delta - 1) < 1e-10) break;
+  }
+  return h;
+}
+
+/** Regularised incomplete beta function I_x(a, b) */
+function incompleteBeta(a: number, b: number, x: number): number {
+  if (x <= 0) return 0;
+  if (x >= 1) return 1;
+  const front = Math.exp(
+    logGamma(a + b) - logGamma(a) - logGamma(b) + a * Math.log(x) + b * Math.log(1 - x),
+  );
+  return x < (a + 1) / (a + b + 2)
+    ? (front * betaContinuedFraction(a, b, x)) / a
+    : 1 - (front * betaContinuedFraction(b, a, 1 - x)) / b;
+}
+
+/** Pearson correlation coefficient; NaN when either series has zero variance */
+export function pearson(xs: number[], ys: number[]): number {
+  const n = xs.length;
+  const meanX = xs.reduce((a, b) => a + b, 0) / n;
+  const meanY = ys.reduce((a, b) => a + b, 0) / n;
+  let sxy = 0;
+  let sxx = 0;
+  let syy = 0;
+  for (let i = 0; i < n; i++) {
+    const dx = xs[i] - meanX;
+    const dy = ys[i] - meanY;
+    sxy += dx * dy;
+    sxx += dx * dx;
+    syy += dy * dy;
+  }
+  return sxy / Math.sqrt(sxx * syy);
+}
+
+/**
+ * Two-sided p-value for a Pearson r over n points, from the t statistic
+ * t = r·√((n−2)/(1−r²)) with n−2 degrees of freedom.
+ */
+export function correlationPValue(r: number, n: number): number {
+  const df = n - 2;
+  if (Math.abs(r) >= 1) return 0;
+  const t2 = (r * r * df) / (1 - r * r);
+  return incompleteBeta(df / 2, 0.5, df / (df + t2));
+}
@@ -3,9 +3,28 @@
 export type TargetType = 'protein' | 'dna' | 'rna';
 
 export interface AppData {
-  schema_version: number; // Currently 3
-  api_key: string | null;
+  schema_version: number; // See CURRENT_SCHEMA_VERSION
+  api_keys: ApiKeyEntry[];
+  active_api_key_id: string | null; // falls back to the default entry when unset
   campaigns: Campaign[];
+  templates: RunTemplate[];
+  audit_log: AuditEntry[]; // oldest first, capped at MAX_AUDIT_ENTRIES
+}
+
+export interface ApiKeyEntry {
+  id: string; // UUID
+  label: string;
+  key: string;
+  is_default: boolean; // the key settings.save reads and writes
+}
+
+export interface AuditEntry {
+  id: string; // UUID
+  timestamp: string; // ISO 8601
+  operation: string; // e.g. "create", "rename", "archive", "retry", "cancel"
+  entity_type: 'campaign' | 'run' | 'compound';
+  entity_id: string; // UUID
+  details: string;
 }
 
 export interface Campaign {
@@ -15,9 +34,11 @@ export interface Campaign {
   target_sequence: string;
   target_type: TargetType;
   description: string | null;
+  note: string | null;
   archived: boolean;
   archived_at: string | null; // ISO 8601
   created_at: string; // ISO 8601
+  display_order: number; // user-set sort key; ties fall back to created_at
   runs: Run[];
 }
 
@@ -28,9 +49,20 @@ export interface Run {
   archived: boolean;
   archived_at: string | null;
   params: RunParams;
+  note: string | null;
+  paused: boolean; // stops queued submissions; PENDING compounds wait for resume
   created_at: string;
   completed_at: string | null;
+  display_order: number; // user-set sort key within the campaign
   compounds: Compound[];
+  compacted: CompactedRunSummary | null; // set once archived compounds were dropped from state
+}
+
+/** What remains of a run's compounds after compactArchive removed them */
+export interface CompactedRunSummary {
+  compacted_at: string; // ISO 8601
+  total_compounds: number;
+  compounds_by_status: Partial<Record<JobStatus, number>>;
 }
 
 export interface RunParams {
@@ -38,20 +70,33 @@ export interface RunParams {
   diffusion_samples: number;
   sampling_steps: number;
   step_scale: number;
+  timeout_secs: number | null; // per-run poll timeout; null uses POLL_TIMEOUT_MS
+  mode: RunMode;
 }
 
+/** protein_only predicts the target structure alone; its compounds have no ligands */
+export type RunMode = 'protein_ligand' | 'protein_only';
+
 export const DEFAULT_RUN_PARAMS: RunParams = {
   recycling_steps: 3,
   diffusion_samples: 1,
   sampling_steps: 200,
   step_scale: 1.5,
+  timeout_secs: null,
+  mode: 'protein_ligand',
 };
 
+export interface RunParamsPreset {
+  name: string;
+  params: RunParams;
+}
+
 export interface Compound {
   id: string;
   display_name: string;
   folder_name: string;
-  smiles: string;
+  smiles: string; // first ligand; kept for display, search, and CSV
+  smiles_list: SmilesEntry[]; // every ligand in the prediction
   boltz_job_id: string | null;
   status: JobStatus;
   submitted_at: string | null;
@@ -59,8 +104,23 @@ export interface Compound {
   metrics: CompoundMetrics | null;
   error_message: string | null;
   download_error: string | null;
+  download_attempts: number; // failed downloads since the last success, for retry backoff
+  last_download_attempt_at: string | null; // ISO 8601
+  download_checksum: string | null; // SHA-256 of the downloaded tar.gz
+  cif_checksum: string | null; // SHA-256 of sample_0_structure.cif as stored
+  input_hash: string | null; // SHA-256 of target + SMILES + params, for reuse
+  note: string | null;
+  archived: boolean; // only terminal compounds can be archived
+  archived_at: string | null; // ISO 8601
+}
+
+export interface SmilesEntry {
+  smiles: string;
+  chain_id: string; // 'A' is the target; ligands use B, C, ...
 }
 
+export const DEFAULT_LIGAND_CHAIN = 'B';
+
 export type JobStatus =
   | 'PENDING'
   | 'CREATED'
@@ -68,13 +128,26 @@ export type JobStatus =
   | 'COMPLETED'
   | 'FAILED'
   | 'TIMED_OUT'
-  | 'CANCELLED';
+  | 'CANCELLED'
+  | 'DOWNLOAD_FAILED'; // prediction succeeded but outputs could not be stored
+
+export const ALL_JOB_STATUSES = [
+  'PENDING',
+  'CREATED',
+  'RUNNING',
+  'COMPLETED',
+  'FAILED',
+  'TIMED_OUT',
+  'CANCELLED',
+  'DOWNLOAD_FAILED',
+] as const satisfies readonly JobStatus[];
 
 export const TERMINAL_STATUSES: ReadonlySet<JobStatus> = new Set([
   'COMPLETED',
   'FAILED',
   'TIMED_OUT',
   'CANCELLED',
+  'DOWNLOAD_FAILED',
 ]);
 
 export function isTerminal(status: JobStatus): boolean {
@@ -138,6 +211,7 @@ export interface PredictionResults {
 
 export interface PredictionOutput {
   download_url?: string;
+  checksum?: string; // SHA-256 hex of the archive, if the API provides one
   metrics?: Record<string, unknown>;
 }
 
@@ -150,6 +224,7 @@ export interface CompoundStatusEvent {
   status: JobStatus;
   metrics: CompoundMetrics | null;
   completed_at: string | null;
+  download_error: string | null;
 }
 
 export interface CompoundFilesReadyEvent {
@@ -157,6 +232,44 @@ export interface CompoundFilesReadyEvent {
   run_id: string;
 }
 
+export interface CompoundFilesChangedEvent {
+  compound_id: string;
+  changed_files: string[]; // file names relative to the compound folder
+}
+
+export interface CompoundDownloadProgressEvent {
+  compound_id: string;
+  bytes_downloaded: number;
+  total_bytes: number | null;
+  percent: number | null; // 0–100, null when total is unknown
+}
+
+export interface CompoundDownloadStartedEvent {
+  compound_id: string;
+  run_id: string;
+  campaign_id: string;
+}
+
+export interface CompoundRenamedEvent {
+  compound_id: string;
+  run_id: string;
+  campaign_id: string;
+  display_name: string;
+  folder_name: string;
+}
+
+export interface CampaignMergedEvent {
+  source_campaign_id: string; // removed by the merge
+  target_campaign_id: string;
+  run_ids: string[]; // runs moved into the target
+}
+
+export interface RunArchivedEvent {
+  run_id: string;
+  campaign_id: string;
+  archived_at: string;
+}
+
 export interface RunCompletedEvent {
   run_id: string;
   campaign_id: string;
@@ -166,6 +279,392 @@ export interface RunCompletedEvent {
   failed_count: number;
   timed_out_count: number;
   cancelled_count: number;
+  duration_secs: number | null; // run created_at → completed_at
+  fastest_compound_secs: number | null; // over compounds with submitted_at and completed_at
+  slowest_compound_secs: number | null;
+}
+
+export interface StateRestoredEvent {
+  backup_name: string;
+}
+
+export interface DownloadQueueEntry {
+  compound_id: string;
+  display_name: string;
+  campaign_name: string;
+  run_name: string;
+  boltz_job_id: string | null;
+  download_error: string | null;
+}
+
+export interface DownloadQueueStatus {
+  pending_downloads: DownloadQueueEntry[]; // COMPLETED, no CIF on disk, no error yet
+  failed_downloads: DownloadQueueEntry[]; // a download attempt recorded an error
+}
+
+export interface FailedCompoundEntry {
+  compound_id: string;
+  display_name: string;
+  smiles: string;
+  campaign_name: string;
+  run_name: string;
+  status: JobStatus; // FAILED or TIMED_OUT
+  error_message: string | null;
+  completed_at: string | null;
+}
+
+export interface StateReloadedEvent {
+  root_dir: string;
+  campaign_count: number;
+}
+
+// ── Integrity ───────────────────────────────────────────────────────
+
+export interface ChecksumVerification {
+  matches: boolean | null; // null when no checksum was recorded at download time
+  expected: string | null;
+  actual: string;
+}
+
+export interface CompoundFiles {
+  sample_count: number;
+  has_metrics: boolean;
+  has_pae: number[]; // sample indices with a PAE image
+  has_cif: number[]; // sample indices with a structure CIF
+  total_size_bytes: number;
+}
+
+export interface BestSample {
+  sample_index: number;
+  structure_confidence: number | null;
+  cif_path: string;
+  pae_path: string | null; // null when the PAE image is missing
+}
+
+// ── Export / Import ─────────────────────────────────────────────────
+
+/** Contents of state_export.json inside a campaign archive. */
+export interface CampaignExport {
+  schema_version: number;
+  campaign: Campaign;
+}
+
+export interface ExportSummary {
+  files_included: number;
+  total_bytes: number; // archive size on disk
+  dest_path: string;
+}
+
+export interface ExportProgressEvent {
+  campaign_id: string;
+  current: number;
+  total: number;
+}
+
+// ── Search ──────────────────────────────────────────────────────────
+
+export interface CompoundWithContext extends Compound {
+  campaign_id: string;
+  campaign_name: string;
+  run_id: string;
+  run_name: string;
+}
+
+export type CompoundSearchResult = CompoundWithContext;
+
+export interface CompoundSearchScope {
+  campaign_id?: string;
+  run_id?: string;
+}
+
+export interface RunWithCampaign extends Run {
+  campaign_id: string;
+  campaign_name: string;
+}
+
+export type RunStatusFilter = 'active' | 'completed' | 'any';
+
+export interface CompoundFilter {
+  status?: JobStatus;
+  campaign_id?: string;
+  run_id?: string;
+  min_binding_confidence?: number;
+}
+
+export interface PaginatedCompounds {
+  items: CompoundWithContext[];
+  total: number;
+  page: number; // 0-based
+  page_size: number;
+}
+
+// ── Rankings ────────────────────────────────────────────────────────
+
+export type TopCompoundsScope =
+  | { type: 'run'; run_id: string }
+  | { type: 'campaign'; campaign_id: string };
+
+export interface RankedCompound extends Compound {
+  rank: number; // 1-based
+  campaign_id: string;
+  run_id: string;
+}
+
+// ── Campaign Analytics ──────────────────────────────────────────────
+
+export const BINDING_HISTOGRAM_BUCKETS = 5; // 0–0.2, 0.2–0.4, …, 0.8–1.0
+
+export interface BestBindingCompound {
+  compound_id: string;
+  run_id: string;
+  binding_confidence: number;
+}
+
+export interface CampaignMetricsSummary {
+  best_binding: BestBindingCompound | null;
+  mean_binding_confidence: number | null;
+  stddev_binding_confidence: number | null; // population
+  binding_histogram: number[]; // BINDING_HISTOGRAM_BUCKETS counts
+  top_by_optimization: RankedCompound[]; // top 5
+  completed_count: number;
+  failed_count: number;
+  running_count: number; // any non-terminal status
+}
+
+/** Share (0–100) of the campaign's scored compounds with a strictly lower score */
+export interface MetricsPercentile {
+  binding_confidence_percentile: number;
+  optimization_score_percentile: number;
+  sample_count_in_campaign: number; // completed compounds with affinity metrics
+}
+
+// ── Summaries ───────────────────────────────────────────────────────
+
+export interface CompoundSummary {
+  id: string;
+  display_name: string;
+  status: JobStatus;
+  binding_confidence: number | null;
+}
+
+export interface RunSummary extends Omit<Run, 'compounds'> {
+  compounds: CompoundSummary[];
+}
+
+// ── Run Pagination ──────────────────────────────────────────────────
+
+export interface PaginatedRunCompounds {
+  run_id: string;
+  display_name: string;
+  params: RunParams;
+  created_at: string;
+  completed_at: string | null;
+  compounds: Compound[];
+  total_compounds: number;
+  filtered_total: number;
+}
+
+// ── Run Progress ────────────────────────────────────────────────────
+
+export interface RunProgress {
+  total: number;
+  terminal: number;
+  percent: number; // 0–100
+  estimated_remaining_secs: number | null;
+}
+
+/** SampleMetrics fields that hold a single number */
+export const NUMERIC_SAMPLE_METRICS = [
+  'structure_confidence',
+  'iptm',
+  'ligand_iptm',
+  'complex_plddt',
+  'ptm',
+  'protein_iptm',
+  'complex_iplddt',
+  'complex_pde',
+  'complex_ipde',
+] as const;
+
+/** Affinity metrics plus the numeric sample metrics (read from sample 0) */
+export type MetricField =
+  | 'binding_confidence'
+  | 'optimization_score'
+  | (typeof NUMERIC_SAMPLE_METRICS)[number];
+
+export interface HistogramBucket {
+  lower: number;
+  upper: number;
+  count: number;
+}
+
+export interface Histogram {
+  buckets: HistogramBucket[]; // empty when there is no data
+  count: number;
+  min: number | null; // null when there is no data
+  max: number | null;
+  mean: number | null;
+  median: number | null;
+}
+
+export interface MetricStatistics {
+  count: number; // compounds with a value for this metric
+  min: number | null;
+  max: number | null;
+  mean: number | null;
+  median: number | null;
+  stddev: number | null; // population
+  histogram: number[]; // equal-width buckets spanning [min, max]
+}
+
+export interface RunStatistics {
+  run_id: string;
+  completed_count: number;
+  bucket_count: number;
+  /** binding_confidence, optimization_score, and each numeric sample-0 metric */
+  metrics: Record<string, MetricStatistics>;
+}
+
+export interface CorrelationResult {
+  r: number; // Pearson correlation coefficient
+  n: number; // compounds with both metrics
+  p_value_approx: number; // two-sided, from the t distribution
+}
+
+export interface SuggestionHint {
+  base_smiles: string; // best-scoring compound that has the feature
+  reason: string;
+}
+
+export interface SubmissionEstimate {
+  compound_count: number;
+  estimated_gpu_hours: number; // approximate; see estimateGpuSeconds
+  estimated_credits: number | null; // null until the API publishes pricing
+}
+
+export interface CompoundPreview {
+  name: string;
+  folder_name: string; // as it would be created inside the run folder
+  smiles_list: SmilesEntry[]; // empty for protein_only runs
+}
+
+export interface InvalidCompoundPreview {
+  name: string;
+  error: string;
+}
+
+export interface RunPreview {
+  folder_name: string;
+  compound_count: number; // valid + invalid
+  valid_compounds: CompoundPreview[];
+  invalid_compounds: InvalidCompoundPreview[];
+  estimated_credits: number | null; // null until the API publishes pricing
+}
+
+export interface CompoundTimeline {
+  compound_id: string;
+  display_name: string;
+  submitted_at: string | null; // null if never submitted
+  completed_at: string | null; // null if never submitted or still running
+  duration_secs: number | null;
+  final_status: JobStatus; // current status for compounds still running
+}
+
+export interface RunTimeline {
+  run_id: string;
+  created_at: string;
+  compounds: CompoundTimeline[]; // by submitted_at, never-submitted last
+}
+
+// ── Workspace Stats ─────────────────────────────────────────────────
+
+// Compounds have no creation time of their own, so submission stands in for it
+export type ActivityEventType =
+  | 'run_created'
+  | 'run_completed'
+  | 'compound_submitted'
+  | 'compound_completed';
+
+export interface ActivityEvent {
+  event_type: ActivityEventType;
+  entity_id: string; // run or compound ID
+  entity_name: string;
+  campaign_id: string;
+  campaign_name: string;
+  run_id: string | null; // set for compound events
+  run_name: string | null;
+  timestamp: string; // ISO 8601
+}
+
+export interface GlobalStats {
+  total_campaigns: number;
+  active_campaigns: number;
+  archived_campaigns: number;
+  total_runs: number;
+  active_runs: number;
+  archived_runs: number;
+  total_compounds: number;
+  compounds_by_status: Record<JobStatus, number>;
+  total_completed: number;
+  /** Completed / terminal compounds, 0 when nothing has finished yet */
+  success_rate: number;
+}
+
+export interface BackupInfo {
+  name: string;
+  size_bytes: number;
+  modified_at: string; // ISO 8601
+}
+
+export interface MissingPath {
+  display_name: string;
+  path: string;
+}
+
+export interface WorkspaceReport {
+  missing_campaign_folders: MissingPath[];
+  missing_run_folders: MissingPath[];
+  missing_compound_outputs: MissingPath[];
+}
+
+export interface OrphanedPath {
+  path: string; // campaign-level or run-level folder with no state entry
+  size_bytes: number | null; // null if the folder couldn't be walked
+}
+
+export interface WorkspaceSize {
+  total_bytes: number;
+  compound_output_bytes: number; // sample_*_structure.cif + sample_*_pae.png
+  state_json_bytes: number; // state.json(.gz), excluding backups
+  temp_bytes: number; // .boltz-temp
+}
+
+export interface PollerStatus {
+  active_compounds: number;
+  api_key_configured: boolean;
+  last_tick_at: string | null; // ISO 8601
+  consecutive_errors: number; // status requests failed in a row
+}
+
+export interface GcSummary {
+  campaigns_compacted: number;
+  runs_compacted: number;
+  compounds_removed: number;
+}
+
+export interface RepairReport {
+  folders_created: number;
+  compounds_queued_for_redownload: number;
+  errors: string[];
+}
+
+export interface FolderSyncReport {
+  folders_synced: number;
+  errors: string[];
+}
+
+export interface PollConfigChangedEvent {
+  interval_secs: number;
 }
 
 // ── Input Types ─────────────────────────────────────────────────────
@@ -173,11 +672,74 @@ export interface RunCompletedEvent {
 export interface CompoundInput {
   name: string;
   smiles: string;
+  ligands?: SmilesEntry[]; // multi-ligand compounds; smiles is ligands[0].smiles
+}
+
+export interface CompoundTemplate {
+  name: string;
+  smiles: string; // empty for protein_only templates
+  ligands?: SmilesEntry[]; // only kept when the compound had several ligands
+}
+
+export interface RunTemplate {
+  id: string; // UUID
+  name: string;
+  description: string | null;
+  params: RunParams;
+  compounds: CompoundTemplate[];
+  created_at: string; // ISO 8601
+}
+
+export interface DuplicateSmilesPair {
+  smiles: string;
+  first: string; // display name of the earlier compound
+  second: string; // display name of the input that repeats it
+}
+
+export interface ImportCompoundsResult {
+  compounds: Compound[];
+  warnings: string[];
+}
+
+export interface SequenceValidationResult {
+  valid: boolean;
+  length: number;
+  error: string | null;
+}
+
+export interface WorkspaceEntry {
+  path: string;
+  last_used: string; // ISO 8601
+}
+
+export interface WorkspaceSwitchedEvent {
+  root_dir: string;
+}
+
+export interface ParamsValidationResult {
+  valid: boolean;
+  error: string | null;
+}
+
+/** All null when the API doesn't report quota (the endpoint returned 404) */
+export interface QuotaInfo {
+  remaining_credits: number | null;
+  used_credits: number | null;
+  resets_at: string | null; // ISO 8601
+  checked_at: string; // ISO 8601
+}
+
+export interface ConnectionInfo {
+  success: boolean;
+  latency_ms: number; // includes any retries
+  api_version: string | null; // from a response header, when the API sends one
+  error: string | null;
 }
 
 export interface SettingsResponse {
   api_key: string | null;
   root_dir: string;
+  proxy_url: string | null; // from prefs; HTTPS_PROXY overrides it
 }
 
 // ── Lightweight Reference (for poller) ──────────────────────────────
@@ -188,19 +750,45 @@ export interface CompoundRef {
   campaign_id: string;
   run_id: string;
   submitted_at: string;
+  run_timeout_secs: number | null; // copied from RunParams so the poller needn't look up the run
 }
 
 // ── Constants ───────────────────────────────────────────────────────
 
+export const CURRENT_SCHEMA_VERSION = 18;
+
 export const POLL_TIMEOUT_MS = 7200_000; // 2 hours
+export const MIN_RUN_TIMEOUT_SECS = 60;
+export const MAX_RUN_TIMEOUT_SECS = 86_400; // 24 hours
 export const POLL_CONCURRENCY = 10;
 export const SUBMIT_CONCURRENCY = 5;
+export const MIN_SUBMIT_CONCURRENCY = 1;
+export const MAX_SUBMIT_CONCURRENCY = 20;
 export const POLL_INTERVAL_MS = 10_000; // 10 seconds
+export const DOWNLOAD_RETRY_INTERVAL_MS = 300_000; // 5 minutes
+export const MAX_DOWNLOAD_ATTEMPTS = 6; // automatic retries stop after this many failures
+export const MIN_POLL_INTERVAL_SECS = 5;
+export const MAX_POLL_INTERVAL_SECS = 300;
+export const MAX_AUTO_ARCHIVE_DAYS = 3650;
 export const FLUSH_INTERVAL_MS = 2_000; // 2 seconds
+export const POLL_DRAIN_MS = 10_000; // grace period for in-flight polls after the poller stops
+export const DEFAULT_MAX_BACKUPS = 5;
+export const BACKUP_MIN_INTERVAL_MS = 300_000; // 5 minutes between state backup rotations
+export const MAX_RECENT_WORKSPACES = 10;
+export const MAX_FASTA_CAMPAIGNS = 50; // per import, to catch accidental huge files
+export const MAX_AUDIT_ENTRIES = 1000;
+export const MIN_CORRELATION_POINTS = 5;
 export const HTTP_TIMEOUT_MS = 30_000; // 30 seconds
+export const QUOTA_CACHE_MS = 300_000; // 5 minutes between quota requests
+export const DOWNLOAD_PROGRESS_THROTTLE_MS = 250;
+export const FOLDER_WATCH_DEBOUNCE_MS = 300;
+export const EXTRACTION_EXPANSION_FACTOR = 3; // tar.gz → extracted size estimate
+export const REMOTE_PREDICTIONS_PAGE_SIZE = 50;
 export const RETRY_ATTEMPTS = 3;
 export const RETRY_BACKOFF_MS = [1000, 2000];
 export const RETRY_JITTER_MS = 500;
 export const RETRY_ATTEMPTS_RATE_LIMIT = 6;
 export const RATE_LIMIT_FALLBACK_MS = 30_000; // when no Retry-After header
+export const CIRCUIT_BREAKER_THRESHOLD = 5; // consecutive failed calls before opening
+export const CIRCUIT_BREAKER_OPEN_MS = 60_000; // 1 minute
 export const BOLTZ_BASE_URL = 'https://lab.boltz.bio';
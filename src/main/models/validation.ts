@@ -0,0 +1,89 @@
+// ── Input Validation ────────────────────────────────────────────────
+
+import type { RunParams, TargetType } from './types';
+import { MIN_RUN_TIMEOUT_SECS, MAX_RUN_TIMEOUT_SECS } from './types';
+
+const MAX_SMILES_LENGTH = 10_000;
+
+/**
+ * Cheap sanity check for SMILES strings — not a chemistry parser, just
+ * catches common copy-paste mistakes. Throws with the specific violation.
+ * Aromatic lowercase atoms (c, n, o, s, p) count as atoms so that
+ * all-aromatic inputs like benzene are accepted.
+ */
+export function validateSmiles(smiles: string): void {
+  if (smiles.length === 0) {
+    throw new Error('SMILES is empty');
+  }
+  if (smiles.trim().length === 0) {
+    throw new Error('SMILES contains only whitespace');
+  }
+  if (smiles.length > MAX_SMILES_LENGTH) {
+    throw new Error(`SMILES exceeds ${MAX_SMILES_LENGTH.toLocaleString()} characters`);
+  }
+  if (!/Br|[CNOSPFI]|[cnosp]/.test(smiles)) {
+    throw new Error('SMILES contains no atoms (expected C, N, O, S, P, F, Cl, Br, or I)');
+  }
+}
+
+const MAX_PROTEIN_LENGTH = 5000;
+const AMINO_ACIDS = new Set('ACDEFGHIKLMNPQRSTVWYX');
+
+/**
+ * Check a protein sequence uses only standard one-letter codes (plus X)
+ * and fits within the Boltz API's practical length limit.
+ */
+export function validateProteinSequence(seq: string): void {
+  if (seq.length === 0) {
+    throw new Error('Protein sequence is empty');
+  }
+  if (seq.length > MAX_PROTEIN_LENGTH) {
+    throw new Error(
+      `Protein sequence has ${seq.length} residues (maximum ${MAX_PROTEIN_LENGTH})`,
+    );
+  }
+  for (let i = 0; i < seq.length; i++) {
+    if (!AMINO_ACIDS.has(seq[i])) {
+      const ch = /\s/.test(seq[i]) ? 'whitespace' : `'${seq[i]}'`;
+      throw new Error(`Invalid character ${ch} at position ${i + 1}`);
+    }
+  }
+}
+
+/** Validate a normalised (trimmed, uppercased) target sequence for its type. */
+export function validateTargetSequence(seq: string, targetType: TargetType): void {
+  if (targetType === 'protein') {
+    validateProteinSequence(seq);
+    return;
+  }
+  if (targetType === 'dna' && !/^[ACGT]+$/.test(seq)) {
+    throw new Error('DNA sequence must contain only A, C, G, and T.');
+  }
+  if (targetType === 'rna' && !/^[ACGU]+$/.test(seq)) {
+    throw new Error('RNA sequence must contain only A, C, G, and U.');
+  }
+}
+
+const RUN_PARAM_RANGES: Record<Exclude<keyof RunParams, 'timeout_secs' | 'mode'>, [number, number]> = {
+  recycling_steps: [1, 10],
+  diffusion_samples: [1, 20],
+  sampling_steps: [1, 500],
+  step_scale: [0.1, 10.0],
+};
+
+/** Reject out-of-range Boltz-2 parameters. The message includes the allowed range. */
+export function validateRunParams(params: RunParams): void {
+  for (const [key, [min, max]] of Object.entries(RUN_PARAM_RANGES)) {
+    const value = params[key as keyof typeof RUN_PARAM_RANGES];
+    if (!(value >= min && value <= max)) {
+      throw new Error(`${key} must be between ${min} and ${max} (got ${value})`);
+    }
+  }
+
+  const timeout = params.timeout_secs;
+  if (timeout != null && !(timeout >= MIN_RUN_TIMEOUT_SECS && timeout <= MAX_RUN_TIMEOUT_SECS)) {
+    throw new Error(
+      `timeout_secs must be between ${MIN_RUN_TIMEOUT_SECS} and ${MAX_RUN_TIMEOUT_SECS} (got ${timeout})`,
+    );
+  }
+}
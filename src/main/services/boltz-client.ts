@@ -1,14 +1,22 @@
+import { Readable, Transform } from 'node:stream';
+import type { ReadableStream as WebReadableStream } from 'node:stream/web';
 import type {
   CompoundMetrics,
   AffinityMetrics,
   SampleMetrics,
   SubmitResponse,
   PredictionStatus,
+  PredictionListResponse,
+  QuotaInfo,
+  RunMode,
   RunParams,
+  SmilesEntry,
   TargetType,
 } from '../models/types';
 import {
   BOLTZ_BASE_URL,
+  CIRCUIT_BREAKER_OPEN_MS,
+  CIRCUIT_BREAKER_THRESHOLD,
   HTTP_TIMEOUT_MS,
   RETRY_ATTEMPTS,
   RETRY_ATTEMPTS_RATE_LIMIT,
@@ -31,6 +39,14 @@ export class BoltzApiError extends Error {
   }
 }
 
+/** Thrown without a network request while the circuit breaker is open. */
+export class CircuitOpenError extends BoltzApiError {
+  constructor() {
+    super('Circuit breaker open');
+    this.name = 'CircuitOpenError';
+  }
+}
+
 function isPermanentError(err: unknown): boolean {
   if (err instanceof BoltzApiError && err.statusCode !== null) {
     const code = err.statusCode;
@@ -57,17 +73,128 @@ function parseRetryAfter(header: string | null): number | null {
   return null;
 }
 
+export interface DownloadStream {
+  stream: Readable;
+  totalBytes: number | null;
+}
+
+export type DownloadProgressCallback = (bytesDownloaded: number, totalBytes: number | null) => void;
+
 export interface RetryOptions {
   onRateLimited?: (delayMs: number) => void;
 }
 
+export interface BoltzClientOptions {
+  breakerThreshold?: number;
+  breakerOpenMs?: number;
+  proxyUrl?: string | null;
+}
+
+type FetchFn = (url: string, init?: RequestInit) => Promise<Response>;
+
+const directFetch: FetchFn = (url, init) => fetch(url, init);
+
+/** Parse a proxy URL, throwing if it isn't one Chromium can use. */
+export function validateProxyUrl(proxyUrl: string): URL {
+  let parsed: URL;
+  try {
+    parsed = new URL(proxyUrl);
+  } catch {
+    throw new Error(`Invalid proxy URL: ${proxyUrl}`);
+  }
+  if (!['http:', 'https:', 'socks4:', 'socks5:'].includes(parsed.protocol)) {
+    throw new Error(`Unsupported proxy scheme: ${parsed.protocol.replace(/:$/, '')}`);
+  }
+  if (parsed.username || parsed.password) {
+    throw new Error('Proxy URLs with credentials are not supported');
+  }
+  return parsed;
+}
+
+/**
+ * fetch routed through an HTTP(S)/SOCKS proxy, using a dedicated in-memory
+ * Electron session (Node's global fetch has no proxy support).
+ */
+function proxiedFetch(proxyUrl: string): FetchFn {
+  const parsed = validateProxyUrl(proxyUrl);
+  const proxyRules = `${parsed.protocol}//${parsed.host}`; // scheme://host:port, no path
+  // Imported here so the client stays loadable outside Electron (unit tests)
+  const ready = import('electron').then(async ({ session }) => {
+    const ses = session.fromPartition(`boltz-proxy:${proxyRules}`);
+    await ses.setProxy({ proxyRules });
+    return ses;
+  });
+  return async (url, init) => (await ready).fetch(url, init);
+}
+
+type CircuitState = 'closed' | 'open' | 'half-open';
+
 // ── BoltzClient ──────────────────────────────────────────────────────
 
 export class BoltzClient {
   private readonly baseUrl: string;
-
-  constructor(baseUrl: string = BOLTZ_BASE_URL) {
+  private readonly breakerThreshold: number;
+  private readonly breakerOpenMs: number;
+  private circuit: CircuitState = 'closed';
+  private consecutiveFailures = 0;
+  private openedAt = 0;
+  private halfOpenInFlight = false;
+  private fetchFn: FetchFn = directFetch;
+
+  constructor(baseUrl: string = BOLTZ_BASE_URL, options: BoltzClientOptions = {}) {
     this.baseUrl = baseUrl.replace(/\/+$/, '');
+    this.breakerThreshold = options.breakerThreshold ?? CIRCUIT_BREAKER_THRESHOLD;
+    this.breakerOpenMs = options.breakerOpenMs ?? CIRCUIT_BREAKER_OPEN_MS;
+    this.setProxy(options.proxyUrl ?? null);
+  }
+
+  /** Route subsequent requests through proxyUrl, or connect directly when null. */
+  setProxy(proxyUrl: string | null): void {
+    this.fetchFn = proxyUrl ? proxiedFetch(proxyUrl) : directFetch;
+  }
+
+  // ── Circuit breaker ──────────────────────────────────────────────
+
+  /** True while requests would be rejected without reaching the network. */
+  get circuitOpen(): boolean {
+    if (this.circuit === 'open') {
+      return Date.now() - this.openedAt < this.breakerOpenMs;
+    }
+    return this.circuit === 'half-open' && this.halfOpenInFlight;
+  }
+
+  /**
+   * Gate a call through the breaker. Once the open period has elapsed,
+   * a single trial call is let through (half-open); its outcome closes
+   * or re-opens the circuit.
+   */
+  private acquireCircuit(): void {
+    if (this.circuit === 'open') {
+      if (Date.now() - this.openedAt < this.breakerOpenMs) throw new CircuitOpenError();
+      this.circuit = 'half-open';
+    }
+    if (this.circuit === 'half-open') {
+      if (this.halfOpenInFlight) throw new CircuitOpenError();
+      this.halfOpenInFlight = true;
+    }
+  }
+
+  private recordSuccess(): void {
+    this.circuit = 'closed';
+    this.consecutiveFailures = 0;
+    this.halfOpenInFlight = false;
+  }
+
+  private recordFailure(): void {
+    this.consecutiveFailures++;
+    if (this.circuit === 'half-open' || this.consecutiveFailures >= this.breakerThreshold) {
+      if (this.circuit !== 'open') {
+        console.warn(`Boltz API circuit breaker open for ${this.breakerOpenMs / 1000}s`);
+      }
+      this.circuit = 'open';
+      this.openedAt = Date.now();
+    }
+    this.halfOpenInFlight = false;
   }
 
   // ── Retry wrapper ────────────────────────────────────────────────
@@ -77,8 +204,24 @@ export class BoltzClient {
    * 429 responses use Retry-After header delay and up to 6 attempts.
    * Other transient errors use fixed backoff and up to 3 attempts.
    * Permanent errors (400, 401, 422) fail immediately.
+   * Calls that still fail after retrying count towards the circuit breaker.
    */
   private async withRetry<T>(fn: () => Promise<T>, opts?: RetryOptions): Promise<T> {
+    this.acquireCircuit();
+    try {
+      const result = await this.retryLoop(fn, opts);
+      this.recordSuccess();
+      return result;
+    } catch (err) {
+      // Permanent errors (bad input, auth) mean the API answered, so they
+      // count as a healthy response rather than an outage
+      if (isPermanentError(err)) this.recordSuccess();
+      else this.recordFailure();
+      throw err;
+    }
+  }
+
+  private async retryLoop<T>(fn: () => Promise<T>, opts?: RetryOptions): Promise<T> {
     let lastErr: unknown = new Error('No attempts made');
     let maxAttempts = RETRY_ATTEMPTS;
 
@@ -137,7 +280,7 @@ export class BoltzClient {
         },
       };
 
-      const resp = await fetch(url, {
+      const resp = await this.fetchFn(url, {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
@@ -167,10 +310,22 @@ export class BoltzClient {
     predictionId: string,
     retryOpts?: RetryOptions,
   ): Promise<PredictionStatus> {
+    return (await this.getPredictionStatusRaw(apiKey, predictionId, retryOpts)) as PredictionStatus;
+  }
+
+  /**
+   * Same request as getPredictionStatus, but returns the response JSON
+   * untouched (for debugging what the API actually reported).
+   */
+  async getPredictionStatusRaw(
+    apiKey: string,
+    predictionId: string,
+    retryOpts?: RetryOptions,
+  ): Promise<unknown> {
     const url = `${this.baseUrl}/api/v1/connect/predictions/${encodeURIComponent(predictionId)}`;
 
     return this.withRetry(async () => {
-      const resp = await fetch(url, {
+      const resp = await this.fetchFn(url, {
         method: 'GET',
         headers: {
           Authorization: `Bearer ${apiKey}`,
@@ -196,20 +351,148 @@ export class BoltzClient {
         );
       }
 
-      return (await resp.json()) as PredictionStatus;
+      return (await resp.json()) as unknown;
     }, retryOpts);
   }
 
   /**
-   * GET {downloadUrl} (presigned, no auth) -- returns Buffer
+   * DELETE /api/v1/connect/predictions/{id} -- stops a job on the API side.
+   * A 404 means the job is already gone or finished and counts as success.
+   */
+  async cancelPrediction(apiKey: string, predictionId: string): Promise<void> {
+    const url = `${this.baseUrl}/api/v1/connect/predictions/${encodeURIComponent(predictionId)}`;
+
+    return this.withRetry(async () => {
+      const resp = await this.fetchFn(url, {
+        method: 'DELETE',
+        headers: {
+          Authorization: `Bearer ${apiKey}`,
+        },
+        signal: AbortSignal.timeout(HTTP_TIMEOUT_MS),
+      });
+
+      if (!resp.ok && resp.status !== 404) {
+        const text = await resp.text().catch(() => '');
+        const retryAfterMs = resp.status === 429
+          ? parseRetryAfter(resp.headers.get('retry-after'))
+          : null;
+        throw new BoltzApiError(
+          `Cancel failed (${resp.status}): ${text}`,
+          resp.status,
+          retryAfterMs,
+        );
+      }
+    });
+  }
+
+  /**
+   * GET /api/v1/connect/quota -- remaining and used credits. A 404 means
+   * the API doesn't report quota; every field comes back null.
    */
-  async downloadTarGz(downloadUrl: string, retryOpts?: RetryOptions): Promise<Buffer> {
+  async getQuota(apiKey: string): Promise<QuotaInfo> {
+    const url = `${this.baseUrl}/api/v1/connect/quota`;
+
     return this.withRetry(async () => {
-      const resp = await fetch(downloadUrl, {
+      const resp = await this.fetchFn(url, {
         method: 'GET',
+        headers: {
+          Authorization: `Bearer ${apiKey}`,
+        },
         signal: AbortSignal.timeout(HTTP_TIMEOUT_MS),
       });
 
+      const checkedAt = new Date().toISOString();
+      if (resp.status === 404) {
+        return { remaining_credits: null, used_credits: null, resets_at: null, checked_at: checkedAt };
+      }
+      if (!resp.ok) {
+        const text = await resp.text().catch(() => '');
+        const retryAfterMs = resp.status === 429
+          ? parseRetryAfter(resp.headers.get('retry-after'))
+          : null;
+        throw new BoltzApiError(
+          `Quota check failed (${resp.status}): ${text}`,
+          resp.status,
+          retryAfterMs,
+        );
+      }
+
+      const json = (await resp.json()) as Record<string, unknown>;
+      const num = (v: unknown) => (typeof v === 'number' && Number.isFinite(v) ? v : null);
+      const resetsAt = typeof json.resets_at === 'string' ? Date.parse(json.resets_at) : NaN;
+      return {
+        remaining_credits: num(json.remaining_credits),
+        used_credits: num(json.used_credits),
+        resets_at: Number.isNaN(resetsAt) ? null : new Date(resetsAt).toISOString(),
+        checked_at: checkedAt,
+      };
+    });
+  }
+
+  /**
+   * GET /api/v1/connect/predictions?limit={pageSize}&offset={page * pageSize}
+   * Page numbers are 0-based.
+   */
+  async listPredictionsPaged(
+    apiKey: string,
+    page: number,
+    pageSize: number,
+  ): Promise<PredictionListResponse> {
+    const params = new URLSearchParams({
+      limit: String(pageSize),
+      offset: String(page * pageSize),
+    });
+    const url = `${this.baseUrl}/api/v1/connect/predictions?${params}`;
+
+    return this.withRetry(async () => {
+      const resp = await this.fetchFn(url, {
+        method: 'GET',
+        headers: {
+          Authorization: `Bearer ${apiKey}`,
+        },
+        signal: AbortSignal.timeout(HTTP_TIMEOUT_MS),
+      });
+
+      if (!resp.ok) {
+        const text = await resp.text().catch(() => '');
+        const retryAfterMs = resp.status === 429
+          ? parseRetryAfter(resp.headers.get('retry-after'))
+          : null;
+        throw new BoltzApiError(
+          `List predictions failed (${resp.status}): ${text}`,
+          resp.status,
+          retryAfterMs,
+        );
+      }
+
+      return (await resp.json()) as PredictionListResponse;
+    });
+  }
+
+  /**
+   * GET {downloadUrl} (presigned, no auth) -- returns the body as a stream.
+   * Only opening the connection is retried; errors mid-body surface on the
+   * stream. onProgress reports bytes received; totalBytes comes from
+   * Content-Length when present.
+   */
+  async downloadTarGz(
+    downloadUrl: string,
+    onProgress?: DownloadProgressCallback,
+    retryOpts?: RetryOptions,
+  ): Promise<DownloadStream> {
+    return this.withRetry(async () => {
+      // A fixed deadline would cut off large archives mid-transfer, so time
+      // out waiting for headers and then only when the body stalls
+      const controller = new AbortController();
+      const timedOut = () => controller.abort(new Error('Download timed out'));
+      const headerTimer = setTimeout(timedOut, HTTP_TIMEOUT_MS);
+      let resp: Response;
+      try {
+        resp = await this.fetchFn(downloadUrl, { method: 'GET', signal: controller.signal });
+      } finally {
+        clearTimeout(headerTimer);
+      }
+
       if (!resp.ok) {
         const retryAfterMs = resp.status === 429
           ? parseRetryAfter(resp.headers.get('retry-after'))
@@ -217,8 +500,28 @@ export class BoltzClient {
         throw new BoltzApiError(`Download failed (${resp.status})`, resp.status, retryAfterMs);
       }
 
-      const arrayBuffer = await resp.arrayBuffer();
-      return Buffer.from(arrayBuffer);
+      if (!resp.body) {
+        throw new BoltzApiError('Download failed: empty response body', resp.status);
+      }
+
+      const lengthHeader = resp.headers.get('content-length');
+      const totalBytes = lengthHeader ? Number(lengthHeader) || null : null;
+
+      const idleTimer = setTimeout(timedOut, HTTP_TIMEOUT_MS);
+      let downloaded = 0;
+      const progress = new Transform({
+        transform(chunk: Buffer, _encoding, callback) {
+          idleTimer.refresh();
+          downloaded += chunk.length;
+          onProgress?.(downloaded, totalBytes);
+          callback(null, chunk);
+        },
+      });
+      progress.on('close', () => clearTimeout(idleTimer));
+
+      const body = Readable.fromWeb(resp.body as WebReadableStream<Uint8Array>);
+      body.on('error', (err) => progress.destroy(err));
+      return { stream: body.pipe(progress), totalBytes };
     }, retryOpts);
   }
 
@@ -226,10 +529,19 @@ export class BoltzClient {
    * GET /api/v1/connect/predictions?limit=1 -- throws on non-2xx
    */
   async testConnection(apiKey: string): Promise<true> {
+    await this.probeConnection(apiKey);
+    return true;
+  }
+
+  /**
+   * Same request as testConnection; resolves with the API version when the
+   * response carries an X-API-Version / API-Version header.
+   */
+  async probeConnection(apiKey: string): Promise<{ apiVersion: string | null }> {
     const url = `${this.baseUrl}/api/v1/connect/predictions?limit=1`;
 
     return this.withRetry(async () => {
-      const resp = await fetch(url, {
+      const resp = await this.fetchFn(url, {
         method: 'GET',
         headers: {
           Authorization: `Bearer ${apiKey}`,
@@ -239,13 +551,18 @@ export class BoltzClient {
 
       if (!resp.ok) {
         const text = await resp.text().catch(() => '');
+        const retryAfterMs = resp.status === 429
+          ? parseRetryAfter(resp.headers.get('retry-after'))
+          : null;
         throw new BoltzApiError(
           `Connection test failed (${resp.status}): ${text}`,
           resp.status,
+          retryAfterMs,
         );
       }
 
-      return true;
+      const apiVersion = resp.headers.get('x-api-version') ?? resp.headers.get('api-version');
+      return { apiVersion };
     });
   }
 }
@@ -264,23 +581,62 @@ export function buildInferenceInput(
   targetType: TargetType = 'protein',
   ligandChainId: string = 'B',
 ): unknown {
+  return buildInferenceInputMulti(sequence, [{ smiles, chain_id: ligandChainId }], targetType);
+}
+
+/**
+ * Build inference input with one ligand entity per SmilesEntry.
+ * Affinity is requested for the first ligand only — the API accepts a
+ * single binder per prediction.
+ */
+export function buildInferenceInputMulti(
+  sequence: string,
+  ligands: SmilesEntry[],
+  targetType: TargetType = 'protein',
+): unknown {
+  if (ligands.length === 0) throw new Error('At least one ligand is required');
+
   const payload: Record<string, unknown> = {
     // version field is ignored by API as of Mar 2026, kept for forward-compat
     version: 2,
     sequences: [
       { [targetType]: { id: 'A', sequence } },
-      { ligand: { id: ligandChainId, smiles } },
+      ...ligands.map((l) => ({ ligand: { id: l.chain_id, smiles: l.smiles } })),
     ],
   };
 
   // Affinity metrics are only supported for protein targets
   if (targetType === 'protein') {
-    payload.properties = [{ affinity: { binder: ligandChainId } }];
+    payload.properties = [{ affinity: { binder: ligands[0].chain_id } }];
   }
 
   return payload;
 }
 
+/** Inference input with only the target entity, for structure-only predictions. */
+export function buildInferenceInputProteinOnly(
+  sequence: string,
+  targetType: TargetType = 'protein',
+): unknown {
+  return {
+    // version field is ignored by API as of Mar 2026, kept for forward-compat
+    version: 2,
+    sequences: [{ [targetType]: { id: 'A', sequence } }],
+  };
+}
+
+/** Pick the inference input builder for a run's mode. */
+export function buildInferenceInputForMode(
+  mode: RunMode,
+  sequence: string,
+  ligands: SmilesEntry[],
+  targetType: TargetType = 'protein',
+): unknown {
+  return mode === 'protein_only'
+    ? buildInferenceInputProteinOnly(sequence, targetType)
+    : buildInferenceInputMulti(sequence, ligands, targetType);
+}
+
 // ── Inference options builder ────────────────────────────────────────
 
 export function buildInferenceOptions(params: RunParams): unknown {
@@ -292,6 +648,37 @@ export function buildInferenceOptions(params: RunParams): unknown {
   };
 }
 
+// ── Cost estimate ────────────────────────────────────────────────────
+
+// Rough fit for Boltz-2 on a single A100; real timings vary with load and
+// ligand size. Only meant to tell a 10-minute batch from a 10-hour one.
+const GPU_OVERHEAD_SECS = 5; // model load and featurisation per prediction
+const TRUNK_SECS_PER_TOKEN_SQ = 2e-5; // pairwise trunk, per recycling pass
+const DIFFUSION_SECS_PER_TOKEN_STEP = 1e-4; // per sampling step, per sample
+
+/** Approximate token count: one per residue or nucleotide, one per ligand heavy atom */
+function estimateTokens(targetLength: number, smiles: string[]): number {
+  const atoms = smiles.reduce((n, s) => n + (s.match(/Cl|Br|[BCNOSPFI]|[bcnosp]/g)?.length ?? 0), 0);
+  return targetLength + atoms;
+}
+
+/**
+ * Approximate GPU seconds for one prediction. The trunk scales with
+ * tokens² per recycling pass (plus the initial pass); diffusion scales
+ * linearly with tokens × sampling_steps × diffusion_samples.
+ */
+export function estimateGpuSeconds(
+  targetLength: number,
+  smiles: string[],
+  params: RunParams,
+): number {
+  const tokens = estimateTokens(targetLength, smiles);
+  const trunk = TRUNK_SECS_PER_TOKEN_SQ * tokens ** 2 * (params.recycling_steps + 1);
+  const diffusion =
+    DIFFUSION_SECS_PER_TOKEN_STEP * tokens * params.sampling_steps * params.diffusion_samples;
+  return GPU_OVERHEAD_SECS + trunk + diffusion;
+}
+
 // ── Prediction name builder ──────────────────────────────────────────
 
 const MAX_PREDICTION_NAME_LENGTH = 128;
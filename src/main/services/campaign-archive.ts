@@ -0,0 +1,213 @@
+import fs from 'node:fs';
+import path from 'node:path';
+import { v4 as uuidv4 } from 'uuid';
+import type {
+  Campaign,
+  CampaignExport,
+  ExportProgressEvent,
+  ExportSummary,
+} from '../models/types';
+import { CURRENT_SCHEMA_VERSION } from '../models/types';
+import { nextDisplayOrder } from '../models/state';
+import type { AppServices } from './index';
+import {
+  migrateAppData,
+  persistState,
+  sanitiseFolderName,
+  uniqueFolderName,
+  validateFolderName,
+} from './storage';
+import { readZip, ZipWriter } from './zip';
+import type { ZipEntry } from './zip';
+
+export const EXPORT_MANIFEST = 'state_export.json';
+
+// ── Export ───────────────────────────────────────────────────────────
+
+/**
+ * Package a campaign into a zip at destPath:
+ * - state_export.json: the campaign subtree with its schema version
+ * - <run folder>/<compound folder>/<file> for each COMPLETED compound
+ *   whose output folder exists
+ * Written to a temp file and renamed, so a failed export leaves nothing behind.
+ */
+export async function exportCampaignZip(
+  services: AppServices,
+  campaignId: string,
+  destPath: string,
+): Promise<ExportSummary> {
+  const { state, eventBus } = services;
+  const campaign = state.findCampaign(campaignId);
+  if (!campaign) throw new Error('Campaign not found');
+  if (!path.isAbsolute(destPath)) throw new Error('Export path must be absolute');
+
+  // Snapshot the campaign and its file list before any await
+  const manifest: CampaignExport = {
+    schema_version: CURRENT_SCHEMA_VERSION,
+    campaign: structuredClone(campaign),
+  };
+  const campaignDir = path.join(state.rootDir, campaign.folder_name);
+  const files: { entryName: string; filePath: string }[] = [];
+  for (const run of campaign.runs) {
+    for (const compound of run.compounds) {
+      if (compound.status !== 'COMPLETED') continue;
+      const compoundDir = path.join(campaignDir, run.folder_name, compound.folder_name);
+      if (!fs.existsSync(compoundDir)) continue;
+
+      for (const entry of fs.readdirSync(compoundDir, { withFileTypes: true })) {
+        if (!entry.isFile()) continue;
+        files.push({
+          entryName: `${run.folder_name}/${compound.folder_name}/${entry.name}`,
+          filePath: path.join(compoundDir, entry.name),
+        });
+      }
+    }
+  }
+
+  const tmpPath = `${destPath}.tmp`;
+  const writer = new ZipWriter(tmpPath);
+  try {
+    await writer.add(EXPORT_MANIFEST, Buffer.from(JSON.stringify(manifest, null, 2), 'utf-8'));
+
+    for (let i = 0; i < files.length; i++) {
+      await writer.add(files[i].entryName, fs.readFileSync(files[i].filePath));
+
+      const progress: ExportProgressEvent = {
+        campaign_id: campaignId,
+        current: i + 1,
+        total: files.length,
+      };
+      eventBus.emit('export-progress', progress);
+    }
+    writer.close();
+  } catch (e) {
+    writer.abort();
+    fs.rmSync(tmpPath, { force: true });
+    throw e;
+  }
+  fs.renameSync(tmpPath, destPath);
+
+  return {
+    files_included: files.length,
+    total_bytes: writer.size,
+    dest_path: destPath,
+  };
+}
+
+// ── Import ───────────────────────────────────────────────────────────
+
+/**
+ * Parse and check an export archive without touching state or disk.
+ * Every file entry must sit under a run/compound folder named in the manifest.
+ */
+function readCampaignArchive(zipPath: string): {
+  campaign: Campaign;
+  outputs: Map<string, ZipEntry[]>; // "run/compound" → files
+} {
+  const entries = readZip(fs.readFileSync(zipPath));
+
+  const manifestEntry = entries.find((e) => e.name === EXPORT_MANIFEST);
+  if (!manifestEntry) throw new Error(`Archive is missing ${EXPORT_MANIFEST}`);
+
+  let manifest: CampaignExport;
+  try {
+    manifest = JSON.parse(manifestEntry.read().toString('utf-8'));
+  } catch {
+    throw new Error(`${EXPORT_MANIFEST} is not valid JSON`);
+  }
+  if (!manifest?.campaign || !Array.isArray(manifest.campaign.runs)) {
+    throw new Error(`${EXPORT_MANIFEST} does not contain a campaign`);
+  }
+
+  // Reuse the workspace migration by wrapping the campaign as AppData
+  const [campaign] = migrateAppData({
+    schema_version: manifest.schema_version,
+    api_keys: [],
+    active_api_key_id: null,
+    campaigns: [manifest.campaign],
+    templates: [],
+    audit_log: [],
+  }).campaigns;
+
+  const known = new Set<string>();
+  for (const run of campaign.runs) {
+    validateFolderName(run.folder_name);
+    for (const compound of run.compounds) {
+      validateFolderName(compound.folder_name);
+      known.add(`${run.folder_name}/${compound.folder_name}`);
+    }
+  }
+
+  const outputs = new Map<string, ZipEntry[]>();
+  for (const entry of entries) {
+    if (entry === manifestEntry || entry.isDirectory) continue;
+    const parts = entry.name.split('/');
+    const key = parts.slice(0, 2).join('/');
+    if (parts.length !== 3 || !known.has(key)) {
+      throw new Error(`Unexpected file in archive: ${entry.name}`);
+    }
+    validateFolderName(parts[2]);
+    const list = outputs.get(key) ?? [];
+    list.push(entry);
+    outputs.set(key, list);
+  }
+
+  return { campaign, outputs };
+}
+
+/**
+ * Restore a campaign from an export archive as a new campaign.
+ * IDs are regenerated so the same archive can be imported twice.
+ * Compounds with output files become COMPLETED; the rest become FAILED.
+ */
+export function importCampaignZip(services: AppServices, zipPath: string): Campaign {
+  const { state } = services;
+  const { campaign, outputs } = readCampaignArchive(zipPath);
+
+  const existing = state.data.campaigns.map((c) => c.folder_name);
+  campaign.id = uuidv4();
+  campaign.folder_name = uniqueFolderName(sanitiseFolderName(campaign.display_name), existing);
+  campaign.display_order = nextDisplayOrder(state.data.campaigns);
+
+  const now = new Date().toISOString();
+  for (const run of campaign.runs) {
+    run.id = uuidv4();
+    for (const compound of run.compounds) {
+      compound.id = uuidv4();
+      compound.download_error = null;
+      compound.download_attempts = 0;
+      compound.last_download_attempt_at = null;
+      if (outputs.has(`${run.folder_name}/${compound.folder_name}`)) {
+        compound.status = 'COMPLETED';
+        compound.completed_at ??= now;
+      } else {
+        compound.status = 'FAILED';
+        compound.completed_at ??= now;
+        compound.error_message ??= 'No output files in imported archive';
+      }
+    }
+  }
+
+  const campaignDir = path.join(state.rootDir, campaign.folder_name);
+  try {
+    for (const run of campaign.runs) {
+      fs.mkdirSync(path.join(campaignDir, run.folder_name), { recursive: true });
+    }
+    for (const [key, files] of outputs) {
+      const compoundDir = path.join(campaignDir, key);
+      fs.mkdirSync(compoundDir, { recursive: true });
+      for (const file of files) {
+        fs.writeFileSync(path.join(campaignDir, file.name), file.read());
+      }
+    }
+  } catch (e) {
+    fs.rmSync(campaignDir, { recursive: true, force: true });
+    throw e;
+  }
+
+  state.data.campaigns.push(campaign);
+  state.audit('import', 'campaign', campaign.id, `from ${path.basename(zipPath)}`);
+  persistState(state.rootDir, state.data);
+
+  return campaign;
+}
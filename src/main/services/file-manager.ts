@@ -1,83 +1,109 @@
 import fs from 'node:fs';
 import path from 'node:path';
 import zlib from 'node:zlib';
-import { Readable } from 'node:stream';
+import { Readable, Transform } from 'node:stream';
+import { pipeline } from 'node:stream/promises';
+import { createHash } from 'node:crypto';
 import * as tar from 'tar';
-import type { CompoundRef, CompoundFilesReadyEvent } from '../models/types';
+import type {
+  CompoundFiles,
+  CompoundRef,
+  CompoundFilesReadyEvent,
+  CompoundStatusEvent,
+  CompoundDownloadProgressEvent,
+} from '../models/types';
+import { DOWNLOAD_PROGRESS_THROTTLE_MS, EXTRACTION_EXPANSION_FACTOR } from '../models/types';
 import type { AppServices } from './index';
 import type { BoltzClient } from './boltz-client';
-import { resolveCompoundPath } from './storage';
+import { checkAvailableSpace, resolveCompoundPath } from './storage';
 
 // ── Extraction ───────────────────────────────────────────────────────
 
 /**
- * Extract tar.gz buffer to tempDir.
+ * Stream a tar.gz into tempDir without buffering the whole archive.
  * - Strips top-level directory from entries
  * - Zip-slip protection (reject ".." components)
  * - Renames: _predicted_structure. -> _structure., _pae_visualization. -> _pae.
+ * Resolves once every entry has been flushed to disk.
  */
-export async function extractTarGz(bytes: Buffer, tempDir: string): Promise<void> {
+export async function extractTarGz(source: Readable, tempDir: string): Promise<void> {
   fs.mkdirSync(tempDir, { recursive: true });
 
-  await new Promise<void>((resolve, reject) => {
-    const extractor = new tar.Parser({
-      onReadEntry(entry: tar.ReadEntry) {
-        const entryPath = entry.path;
-        const components = entryPath.split('/').filter((c: string) => c !== '');
-
-        // Strip top-level directory (e.g., "prediction_abc123/")
-        if (components.length <= 1) {
-          entry.resume();
-          return;
-        }
-
-        const relativeComponents = components.slice(1);
-
-        // Zip-slip protection: reject entries with ".." components
-        if (relativeComponents.some((c: string) => c === '..')) {
-          entry.resume();
-          reject(
-            new Error(`Path traversal detected in archive entry: ${relativeComponents.join('/')}`),
-          );
-          return;
-        }
-
-        // Rename per convention
-        let filename = relativeComponents.join('/');
-        filename = filename.replace(/_predicted_structure\./g, '_structure.');
-        filename = filename.replace(/_pae_visualization\./g, '_pae.');
-
-        const dest = path.join(tempDir, filename);
-        const parentDir = path.dirname(dest);
-        fs.mkdirSync(parentDir, { recursive: true });
-
-        // If this is a directory entry, just create it
-        if (entry.type === 'Directory') {
-          fs.mkdirSync(dest, { recursive: true });
-          entry.resume();
-          return;
-        }
-
-        // Collect file content and write
-        const chunks: Buffer[] = [];
-        entry.on('data', (chunk: Buffer) => chunks.push(chunk));
-        entry.on('end', () => {
-          const content = Buffer.concat(chunks);
-          fs.writeFileSync(dest, content);
-        });
-      },
-    });
-
-    extractor.on('end', resolve);
-    extractor.on('error', reject);
-
-    // Pipe the gzipped buffer through the tar parser
-    const readable = Readable.from(bytes);
-    const gunzip = zlib.createGunzip();
-
-    readable.pipe(gunzip).pipe(extractor);
-    gunzip.on('error', reject);
+  const writes: Promise<void>[] = [];
+  let traversal: Error | null = null;
+  const extractor = new tar.Parser({
+    onReadEntry(entry: tar.ReadEntry) {
+      if (traversal) {
+        entry.resume();
+        return;
+      }
+      const entryPath = entry.path;
+      const components = entryPath.split('/').filter((c: string) => c !== '');
+
+      // Strip top-level directory (e.g., "prediction_abc123/")
+      if (components.length <= 1) {
+        entry.resume();
+        return;
+      }
+
+      const relativeComponents = components.slice(1);
+
+      // Zip-slip protection: reject entries with ".." components
+      if (relativeComponents.some((c: string) => c === '..')) {
+        entry.resume();
+        traversal = new Error(
+          `Path traversal detected in archive entry: ${relativeComponents.join('/')}`,
+        );
+        return;
+      }
+
+      // Rename per convention
+      let filename = relativeComponents.join('/');
+      filename = filename.replace(/_predicted_structure\./g, '_structure.');
+      filename = filename.replace(/_pae_visualization\./g, '_pae.');
+
+      const dest = path.join(tempDir, filename);
+      const parentDir = path.dirname(dest);
+      fs.mkdirSync(parentDir, { recursive: true });
+
+      // If this is a directory entry, just create it
+      if (entry.type === 'Directory') {
+        fs.mkdirSync(dest, { recursive: true });
+        entry.resume();
+        return;
+      }
+
+      // Stream file content straight to disk
+      writes.push(pipeline(entry, fs.createWriteStream(dest)));
+    },
+  });
+
+  await pipeline(source, zlib.createGunzip(), extractor);
+  await Promise.all(writes);
+  if (traversal) throw traversal;
+}
+
+/** Pass-through stream that hashes everything flowing through it. */
+function sha256Tap(): { stream: Transform; digest: () => string } {
+  const hash = createHash('sha256');
+  const stream = new Transform({
+    transform(chunk: Buffer, _encoding, callback) {
+      hash.update(chunk);
+      callback(null, chunk);
+    },
   });
+  return { stream, digest: () => hash.digest('hex') };
+}
+
+// ── Checksums ────────────────────────────────────────────────────────
+
+export function sha256(bytes: Buffer): string {
+  return createHash('sha256').update(bytes).digest('hex');
+}
+
+/** Compare hex digests, tolerating case and an optional "sha256:" prefix. */
+function checksumsMatch(expected: string, actual: string): boolean {
+  return expected.trim().toLowerCase().replace(/^sha256:/, '') === actual;
 }
 
 // ── Validation ───────────────────────────────────────────────────────
@@ -95,24 +121,99 @@ export function validateExtraction(tempDir: string): void {
   }
 }
 
-// ── Download + Extract + Store ───────────────────────────────────────
+/**
+ * Like validateExtraction, but also requires a structure CIF for every
+ * diffusion sample. Used when the run's params are known.
+ */
+export function validateExtractionFull(tempDir: string, expectedSamples: number): void {
+  validateExtraction(tempDir);
+  for (let i = 1; i < expectedSamples; i++) {
+    const file = `sample_${i}_structure.cif`;
+    if (!fs.existsSync(path.join(tempDir, file))) {
+      throw new Error(`Expected file missing after extraction: ${file}`);
+    }
+  }
+}
+
+// ── Inventory ────────────────────────────────────────────────────────
+
+const SAMPLE_FILE_RE = /^sample_(\d+)_(structure\.cif|pae\.png)$/;
 
 /**
- * Set download_error on a compound without changing its status.
- * The compound stays Completed so scanIncompleteDownloads can recover it.
+ * Enumerate the output files in a compound folder.
+ * sample_count counts distinct sample indices with any file present.
  */
-function setDownloadError(services: AppServices, compoundId: string, errorMsg: string): void {
-  const compound = services.state.findCompound(compoundId);
-  if (compound) {
-    compound.download_error = errorMsg;
-    services.state.markDirty();
+export function listCompoundFiles(compoundDir: string): CompoundFiles {
+  const cif = new Set<number>();
+  const pae = new Set<number>();
+  let hasMetrics = false;
+  let totalSize = 0;
+
+  for (const entry of fs.readdirSync(compoundDir, { withFileTypes: true })) {
+    if (!entry.isFile()) continue;
+    totalSize += fs.statSync(path.join(compoundDir, entry.name)).size;
+
+    if (entry.name === 'metrics.json') {
+      hasMetrics = true;
+      continue;
+    }
+    const m = SAMPLE_FILE_RE.exec(entry.name);
+    if (!m) continue;
+    const index = parseInt(m[1], 10);
+    if (m[2] === 'structure.cif') cif.add(index);
+    else pae.add(index);
   }
+
+  const byIndex = (a: number, b: number) => a - b;
+  return {
+    sample_count: new Set([...cif, ...pae]).size,
+    has_metrics: hasMetrics,
+    has_pae: [...pae].sort(byIndex),
+    has_cif: [...cif].sort(byIndex),
+    total_size_bytes: totalSize,
+  };
+}
+
+// ── Download + Extract + Store ───────────────────────────────────────
+
+/**
+ * Move a compound to DOWNLOAD_FAILED with the given error.
+ * scanIncompleteDownloads picks it up for recovery on the next startup.
+ */
+function setDownloadError(services: AppServices, ref: CompoundRef, errorMsg: string): void {
+  const compound = services.state.findCompound(ref.compound_id);
+  if (!compound) return;
+
+  compound.status = 'DOWNLOAD_FAILED';
+  compound.download_error = errorMsg;
+  compound.download_attempts++;
+  compound.last_download_attempt_at = new Date().toISOString();
+  services.state.markDirty();
+
+  const event: CompoundStatusEvent = {
+    compound_id: ref.compound_id,
+    run_id: ref.run_id,
+    campaign_id: ref.campaign_id,
+    status: compound.status,
+    metrics: compound.metrics,
+    completed_at: compound.completed_at,
+    download_error: errorMsg,
+  };
+  services.eventBus.emit('compound-status-changed', event);
+}
+
+// Compounds with a download in progress, so periodic retries don't race it
+const activeDownloads = new Set<string>();
+
+export function isDownloadActive(compoundId: string): boolean {
+  return activeDownloads.has(compoundId);
 }
 
 /**
  * Full download + extract + move flow:
- * 1. Download tar.gz
- * 2. Extract to .boltz-temp/{compoundId}/
+ * 1. Open the tar.gz download stream
+ * 2. Extract to .boltz-temp/{compoundId}/ as bytes arrive, then verify
+ *    expectedChecksum when the API provides one
  * 3. Validate
  * 4. Resolve compound path
  * 5. Move from temp to final (atomic rename)
@@ -123,43 +224,120 @@ export async function downloadAndStore(
   client: BoltzClient,
   downloadUrl: string,
   compoundRef: CompoundRef,
+  expectedChecksum: string | null = null,
+  expectedSamples: number | null = null,
+): Promise<void> {
+  const id = compoundRef.compound_id;
+  if (activeDownloads.has(id)) return; // already being downloaded
+  activeDownloads.add(id);
+  try {
+    await storeDownload(
+      services,
+      client,
+      downloadUrl,
+      compoundRef,
+      expectedChecksum,
+      expectedSamples,
+    );
+  } finally {
+    activeDownloads.delete(id);
+  }
+}
+
+async function storeDownload(
+  services: AppServices,
+  client: BoltzClient,
+  downloadUrl: string,
+  compoundRef: CompoundRef,
+  expectedChecksum: string | null,
+  expectedSamples: number | null,
 ): Promise<void> {
   const rootDir = services.state.rootDir;
 
-  // 1. Download tar.gz
-  let bytes: Buffer;
+  // 1. Download tar.gz, reporting progress (throttled, final chunk always sent)
+  let lastEmit = 0;
+  const onProgress = (bytesDownloaded: number, totalBytes: number | null) => {
+    const now = Date.now();
+    const done = totalBytes !== null && bytesDownloaded >= totalBytes;
+    if (!done && now - lastEmit < DOWNLOAD_PROGRESS_THROTTLE_MS) return;
+    lastEmit = now;
+
+    const event: CompoundDownloadProgressEvent = {
+      compound_id: compoundRef.compound_id,
+      bytes_downloaded: bytesDownloaded,
+      total_bytes: totalBytes,
+      percent: totalBytes ? Math.min(100, (bytesDownloaded / totalBytes) * 100) : null,
+    };
+    services.eventBus.emit('compound-download-progress', event);
+  };
+
+  let source: Readable;
+  let totalBytes: number | null;
   try {
-    bytes = await client.downloadTarGz(downloadUrl);
+    ({ stream: source, totalBytes } = await client.downloadTarGz(downloadUrl, onProgress));
   } catch (e) {
     const msg = e instanceof Error ? e.message : String(e);
     console.error(`Failed to download compound ${compoundRef.compound_id}: ${msg}`);
-    setDownloadError(services, compoundRef.compound_id, `Download failed: ${msg}`);
+    setDownloadError(services, compoundRef, `Download failed: ${msg}`);
     return;
   }
 
-  // 2. Extract to .boltz-temp/{compoundId}/
+  // Skip early if the extracted outputs won't fit (unknown size: proceed)
+  if (totalBytes !== null) {
+    try {
+      checkAvailableSpace(rootDir, totalBytes * EXTRACTION_EXPANSION_FACTOR);
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      console.error(`Skipping download for ${compoundRef.compound_id}: ${msg}`);
+      source.destroy();
+      setDownloadError(services, compoundRef, msg);
+      return;
+    }
+  }
+
+  // 2. Extract to .boltz-temp/{compoundId}/ while hashing the raw bytes
   const tempDir = path.join(rootDir, '.boltz-temp', compoundRef.compound_id);
+  const tap = sha256Tap();
+  // .pipe() doesn't forward errors; without this a dropped body stalls extraction
+  source.on('error', (err) => tap.stream.destroy(err));
 
   try {
-    await extractTarGz(bytes, tempDir);
+    await extractTarGz(source.pipe(tap.stream), tempDir);
   } catch (e) {
     const msg = e instanceof Error ? e.message : String(e);
     console.error(`Failed to extract compound ${compoundRef.compound_id}: ${msg}`);
-    setDownloadError(services, compoundRef.compound_id, `Extraction failed: ${msg}`);
+    source.destroy();
+    fs.rmSync(tempDir, { recursive: true, force: true });
+    setDownloadError(services, compoundRef, `Extraction failed: ${msg}`);
     return;
   }
 
-  // 3. Validate extraction
+  // Verify integrity before anything leaves the temp dir
+  const downloadChecksum = tap.digest();
+  if (expectedChecksum && !checksumsMatch(expectedChecksum, downloadChecksum)) {
+    console.error(`Checksum mismatch for compound ${compoundRef.compound_id}`);
+    fs.rmSync(tempDir, { recursive: true, force: true });
+    setDownloadError(services, compoundRef, 'Download failed: checksum mismatch');
+    return;
+  }
+
+  // 3. Validate extraction (every sample when the run's params are known)
   try {
-    validateExtraction(tempDir);
+    if (expectedSamples !== null) {
+      validateExtractionFull(tempDir, expectedSamples);
+    } else {
+      validateExtraction(tempDir);
+    }
   } catch (e) {
     const msg = e instanceof Error ? e.message : String(e);
     console.error(`Extraction validation failed for ${compoundRef.compound_id}: ${msg}`);
     fs.rmSync(tempDir, { recursive: true, force: true });
-    setDownloadError(services, compoundRef.compound_id, `Extraction validation failed: ${msg}`);
+    setDownloadError(services, compoundRef, `Extraction validation failed: ${msg}`);
     return;
   }
 
+  const cifChecksum = sha256(fs.readFileSync(path.join(tempDir, 'sample_0_structure.cif')));
+
   // 4. Resolve compound path
   let dest: string;
   try {
@@ -169,7 +347,7 @@ export async function downloadAndStore(
     console.error(`Failed to resolve path for ${compoundRef.compound_id}: ${msg}`);
     setDownloadError(
       services,
-      compoundRef.compound_id,
+      compoundRef,
       `Failed to resolve output path: ${msg}`,
     );
     return;
@@ -191,15 +369,33 @@ export async function downloadAndStore(
     console.error(
       `Failed to move compound files from ${tempDir} to ${dest}: ${msg}`,
     );
-    setDownloadError(services, compoundRef.compound_id, 'Failed to store compound files on disk');
+    setDownloadError(services, compoundRef, 'Failed to store compound files on disk');
     return;
   }
 
-  // Success -- clear any previous download error
+  // Success -- clear any previous download error and record checksums
   const compound = services.state.findCompound(compoundRef.compound_id);
   if (compound) {
+    const recovered = compound.status === 'DOWNLOAD_FAILED';
+    compound.status = 'COMPLETED';
     compound.download_error = null;
+    compound.download_attempts = 0;
+    compound.download_checksum = downloadChecksum;
+    compound.cif_checksum = cifChecksum;
     services.state.markDirty();
+
+    if (recovered) {
+      const statusEvent: CompoundStatusEvent = {
+        compound_id: compoundRef.compound_id,
+        run_id: compoundRef.run_id,
+        campaign_id: compoundRef.campaign_id,
+        status: 'COMPLETED',
+        metrics: compound.metrics,
+        completed_at: compound.completed_at,
+        download_error: null,
+      };
+      services.eventBus.emit('compound-status-changed', statusEvent);
+    }
   }
 
   console.log(`Compound ${compoundRef.compound_id} files stored at ${dest}`);
@@ -0,0 +1,68 @@
+import fs from 'node:fs';
+import type { EventEmitter } from 'node:events';
+import type { CompoundFilesChangedEvent } from '../models/types';
+import { FOLDER_WATCH_DEBOUNCE_MS } from '../models/types';
+
+interface ActiveWatch {
+  watcher: fs.FSWatcher;
+  changed: Set<string>;
+  timer: ReturnType<typeof setTimeout> | null;
+}
+
+/**
+ * Watches compound output folders for edits made outside the app (e.g. a
+ * CIF saved from ChimeraX) and emits 'compound-files-changed'. One watcher
+ * per compound; bursts of change events are batched into a single event.
+ */
+export class FolderWatcher {
+  private eventBus: EventEmitter;
+  private watches = new Map<string, ActiveWatch>();
+
+  constructor(eventBus: EventEmitter) {
+    this.eventBus = eventBus;
+  }
+
+  /** Start watching; a second call for the same compound is a no-op */
+  watch(compoundId: string, dir: string): void {
+    if (this.watches.has(compoundId)) return;
+    if (!fs.existsSync(dir)) throw new Error('Compound output folder not found');
+
+    const watcher = fs.watch(dir, (_eventType, filename) => {
+      if (!filename) return;
+      active.changed.add(filename.toString());
+      active.timer ??= setTimeout(() => this.flush(compoundId), FOLDER_WATCH_DEBOUNCE_MS);
+    });
+    watcher.on('error', (err) => {
+      console.warn(`Stopped watching compound ${compoundId}:`, err);
+      this.unwatch(compoundId);
+    });
+
+    const active: ActiveWatch = { watcher, changed: new Set(), timer: null };
+    this.watches.set(compoundId, active);
+  }
+
+  unwatch(compoundId: string): void {
+    const active = this.watches.get(compoundId);
+    if (!active) return;
+    if (active.timer) clearTimeout(active.timer);
+    active.watcher.close();
+    this.watches.delete(compoundId);
+  }
+
+  /** Close every watcher, e.g. on workspace switch or shutdown */
+  unwatchAll(): void {
+    for (const id of [...this.watches.keys()]) this.unwatch(id);
+  }
+
+  private flush(compoundId: string): void {
+    const active = this.watches.get(compoundId);
+    if (!active) return;
+    const event: CompoundFilesChangedEvent = {
+      compound_id: compoundId,
+      changed_files: [...active.changed].sort(),
+    };
+    active.changed.clear();
+    active.timer = null;
+    this.eventBus.emit('compound-files-changed', event);
+  }
+}
@@ -1,21 +1,40 @@
 import { EventEmitter } from 'node:events';
 import { AppState } from '../models/state';
-import { readRootDir } from './prefs';
+import type { StateReloadedEvent, WorkspaceSwitchedEvent } from '../models/types';
+import { POLL_DRAIN_MS } from '../models/types';
+import {
+  readAllPrefs,
+  readBackupMaxCount,
+  readPollIntervalSecs,
+  readSubmissionConcurrency,
+  readCompressState,
+  resolveProxyUrl,
+  writeAllPrefs,
+  touchRecentWorkspace,
+} from './prefs';
 import {
   loadState,
   startPersistenceFlusher,
   cleanupTempDir,
   persistState,
   scanIncompleteDownloads,
+  configureBackups,
+  configureCompression,
+  readStateFromDisk,
+  releaseWorkspaceLock,
 } from './storage';
 import { BoltzClient } from './boltz-client';
 import { Poller, recoverIncompleteDownloads } from './poller';
+import { FolderWatcher } from './folder-watcher';
 
 export class AppServices {
   state: AppState;
   eventBus: EventEmitter;
   client: BoltzClient;
   poller: Poller;
+  folderWatcher: FolderWatcher;
+  /** Max in-flight submissions per batch; read fresh by each submitCompounds call */
+  submitConcurrency: number;
   private stopFlusher: () => void;
 
   private constructor(
@@ -30,10 +49,15 @@ export class AppServices {
     this.stopFlusher = stopFlusher;
     this.client = client;
     this.poller = poller;
+    this.folderWatcher = new FolderWatcher(eventBus);
+    this.submitConcurrency = readSubmissionConcurrency();
   }
 
   static initialize(): AppServices {
-    const rootDir = readRootDir();
+    // MULTIPLEXER_ROOT_DIR overrides prefs without being saved to them
+    const rootDir = process.env.MULTIPLEXER_ROOT_DIR || readAllPrefs().root_dir;
+    configureBackups(readBackupMaxCount());
+    configureCompression(readCompressState());
     const state = loadState(rootDir);
     const eventBus = new EventEmitter();
     const stopFlusher = startPersistenceFlusher(state);
@@ -42,32 +66,121 @@ export class AppServices {
     cleanupTempDir(rootDir);
 
     // Create HTTP client and poller
-    const client = new BoltzClient();
+    const client = new BoltzClient(undefined, { proxyUrl: resolveProxyUrl() });
     const services = new AppServices(state, eventBus, stopFlusher, client, null!);
-    const poller = new Poller(services, client);
+    const poller = new Poller(services, client, readPollIntervalSecs() * 1000);
     services.poller = poller;
 
     // Start the background poller
     poller.start();
 
     // Recover incomplete downloads in the background
-    const incompleteDownloads = scanIncompleteDownloads(rootDir, state.data);
+    services.recoverDownloads();
+    touchRecentWorkspace(rootDir);
+
+    return services;
+  }
+
+  /** Re-download outputs for COMPLETED compounds missing files on disk */
+  private recoverDownloads(): void {
+    const incompleteDownloads = scanIncompleteDownloads(this.state.rootDir, this.state.data);
     if (incompleteDownloads.length > 0) {
-      recoverIncompleteDownloads(services, client, incompleteDownloads).catch((err) => {
+      recoverIncompleteDownloads(this, this.client, incompleteDownloads).catch((err) => {
         console.error('Download recovery failed:', err);
       });
     }
+  }
 
-    return services;
+  /**
+   * Switch to another workspace without restarting: stop polling and give
+   * in-flight polls POLL_DRAIN_MS to settle, flush the old state, load the
+   * new root (taking its lock), then resume polling. The API keys carry
+   * over to the new workspace.
+   */
+  async switchWorkspace(newRootDir: string): Promise<void> {
+    const oldRootDir = this.state.rootDir;
+    if (newRootDir === oldRootDir) return;
+
+    this.poller.stop();
+    this.folderWatcher.unwatchAll();
+    try {
+      if (!(await this.poller.waitIdle(POLL_DRAIN_MS))) {
+        console.warn('Switching workspace with poll tasks still in flight');
+      }
+      if (this.state.dirty) {
+        this.state.dirty = false;
+        persistState(oldRootDir, this.state.data);
+      }
+
+      const { api_keys, active_api_key_id } = this.state.data;
+      const newState = loadState(newRootDir); // throws if locked elsewhere
+      releaseWorkspaceLock(oldRootDir);
+
+      this.state.data = newState.data;
+      this.state.data.api_keys = api_keys;
+      this.state.data.active_api_key_id = active_api_key_id;
+      this.state.rootDir = newRootDir;
+      this.state.markDirty();
+      persistState(newRootDir, this.state.data);
+    } finally {
+      this.poller.start();
+    }
+
+    writeAllPrefs({ ...readAllPrefs(), root_dir: newRootDir });
+    touchRecentWorkspace(newRootDir);
+    cleanupTempDir(newRootDir);
+    this.recoverDownloads();
+
+    const event: WorkspaceSwitchedEvent = { root_dir: newRootDir };
+    this.eventBus.emit('workspace-switched', event);
   }
 
-  /** Graceful shutdown: stop poller, stop flusher, flush dirty state */
-  shutdown(): void {
+  /**
+   * Replace in-memory state with whatever is on disk now (e.g. after the
+   * user restored state.json by hand). Polling is stopped and in-flight
+   * polls get POLL_DRAIN_MS to settle before the swap; unsaved
+   * in-memory changes are discarded. Compounds still in progress in the
+   * loaded state are picked up by the next poll tick.
+   */
+  async reloadStateFromDisk(): Promise<void> {
+    const rootDir = this.state.rootDir;
+    const { data, migrated } = readStateFromDisk(rootDir); // parse before stopping anything
+
     this.poller.stop();
+    this.folderWatcher.unwatchAll();
+    try {
+      if (!(await this.poller.waitIdle(POLL_DRAIN_MS))) {
+        console.warn('Reloading state with poll tasks still in flight');
+      }
+      this.state.data = data;
+      this.state.quota = null;
+      this.state.dirty = false;
+      if (migrated) persistState(rootDir, this.state.data);
+      cleanupTempDir(rootDir);
+    } finally {
+      this.poller.start();
+    }
+    this.recoverDownloads();
+
+    const event: StateReloadedEvent = { root_dir: rootDir, campaign_count: data.campaigns.length };
+    this.eventBus.emit('state-reloaded', event);
+  }
+
+  /**
+   * Graceful shutdown: stop poller, give in-flight polls and downloads
+   * POLL_DRAIN_MS to finish, stop flusher, flush dirty state, release lock
+   */
+  async shutdown(): Promise<void> {
+    this.poller.stop();
+    this.folderWatcher.unwatchAll();
+    if (!(await this.poller.waitIdle(POLL_DRAIN_MS))) {
+      console.warn('Shutting down with poll tasks still in flight');
+    }
     this.stopFlusher();
     if (this.state.dirty) {
       this.state.dirty = false;
       persistState(this.state.rootDir, this.state.data);
     }
+    releaseWorkspaceLock(this.state.rootDir);
   }
 }
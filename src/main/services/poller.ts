@@ -1,38 +1,123 @@
 import pLimit from 'p-limit';
 import type {
+  Compound,
   CompoundRef,
   CompoundStatusEvent,
   CompoundMetrics,
   RunCompletedEvent,
   JobStatus,
+  PollerStatus,
   PredictionStatus,
+  RunArchivedEvent,
+} from '../models/types';
+import {
+  DOWNLOAD_RETRY_INTERVAL_MS,
+  MAX_DOWNLOAD_ATTEMPTS,
+  POLL_INTERVAL_MS,
+  POLL_TIMEOUT_MS,
+  POLL_CONCURRENCY,
 } from '../models/types';
-import { POLL_INTERVAL_MS, POLL_TIMEOUT_MS, POLL_CONCURRENCY } from '../models/types';
 import type { AppServices } from './index';
 import type { BoltzClient } from './boltz-client';
-import { parseMetrics } from './boltz-client';
-import { downloadAndStore } from './file-manager';
+import { CircuitOpenError, parseMetrics } from './boltz-client';
+import { downloadAndStore, isDownloadActive } from './file-manager';
+import { persistState, scanIncompleteDownloads } from './storage';
+import { readAutoArchiveDays } from './prefs';
 import { trackEvent } from './telemetry';
 
+/**
+ * Whether the periodic retry should try a compound's download again. The
+ * wait doubles after each failure (5, 10, 20… minutes) and retries stop
+ * after MAX_DOWNLOAD_ATTEMPTS; the user can still retry by hand.
+ */
+function downloadRetryDue(compound: Compound, now: number): boolean {
+  if (compound.download_attempts === 0 || !compound.last_download_attempt_at) return true;
+  if (compound.download_attempts >= MAX_DOWNLOAD_ATTEMPTS) return false;
+  const waitMs = DOWNLOAD_RETRY_INTERVAL_MS * 2 ** (compound.download_attempts - 1);
+  return now - new Date(compound.last_download_attempt_at).getTime() >= waitMs;
+}
+
+/**
+ * How often to re-check a compound based on how long ago it was submitted.
+ * Entries are ordered by `afterMs`; the last matching entry wins. Intervals
+ * never drop below the configured poll interval.
+ */
+const POLL_BACKOFF_SCHEDULE: readonly { afterMs: number; intervalMs: number }[] = [
+  { afterMs: 0, intervalMs: 0 }, // first 5 minutes: every tick
+  { afterMs: 5 * 60_000, intervalMs: 30_000 }, // 5–30 minutes
+  { afterMs: 30 * 60_000, intervalMs: 60_000 }, // 30+ minutes
+];
+
+function backoffIntervalMs(elapsedMs: number, pollIntervalMs: number): number {
+  let interval = POLL_BACKOFF_SCHEDULE[0].intervalMs;
+  for (const step of POLL_BACKOFF_SCHEDULE) {
+    if (elapsedMs >= step.afterMs) interval = step.intervalMs;
+  }
+  return Math.max(pollIntervalMs, interval);
+}
+
+/** Per-run timeout when set, otherwise the global POLL_TIMEOUT_MS */
+function timeoutMs(ref: CompoundRef): number {
+  return ref.run_timeout_secs != null ? ref.run_timeout_secs * 1000 : POLL_TIMEOUT_MS;
+}
+
+function formatTimeout(ms: number): string {
+  const mins = Math.round(ms / 60_000);
+  if (mins % 60 === 0) {
+    const hours = mins / 60;
+    return `${hours} hour${hours === 1 ? '' : 's'}`;
+  }
+  return `${mins} minute${mins === 1 ? '' : 's'}`;
+}
+
 export class Poller {
   private services: AppServices;
   private client: BoltzClient;
   private timer: ReturnType<typeof setInterval> | null = null;
+  private retryTimer: ReturnType<typeof setInterval> | null = null;
+  private retrying = false;
   private limit = pLimit(POLL_CONCURRENCY);
-
-  constructor(services: AppServices, client: BoltzClient) {
+  private intervalMs: number;
+  /** compound_id → epoch ms of the last status request */
+  private lastChecked = new Map<string, number>();
+  private lastTickAt: string | null = null;
+  private activeCompounds = 0;
+  private consecutiveErrors = 0;
+  /** Ticks and download retries still running; see waitIdle */
+  private inFlight = 0;
+  private idleWaiters: (() => void)[] = [];
+  /** Archive runs this many days after completion; null disables it */
+  private autoArchiveDays: number | null = readAutoArchiveDays();
+
+  constructor(services: AppServices, client: BoltzClient, intervalMs: number = POLL_INTERVAL_MS) {
     this.services = services;
     this.client = client;
+    this.intervalMs = intervalMs;
   }
 
-  /** Start 10-second polling loop */
+  /**
+   * Start the polling loop (10 seconds unless configured in prefs) and the
+   * 5-minute failed-download retry loop alongside it.
+   */
   start(): void {
     if (this.timer) return;
     this.timer = setInterval(() => {
-      this.pollTick().catch((err) => {
+      this.track(this.pollTick()).catch((err) => {
         console.error('Poller tick error:', err);
       });
-    }, POLL_INTERVAL_MS);
+    }, this.intervalMs);
+    this.retryTimer = setInterval(() => {
+      this.track(this.retryDownloads()).catch((err) => {
+        console.error('Download retry error:', err);
+      });
+    }, DOWNLOAD_RETRY_INTERVAL_MS);
+  }
+
+  /** Restart the loop with a new interval */
+  restart(intervalMs: number): void {
+    this.stop();
+    this.intervalMs = intervalMs;
+    this.start();
   }
 
   /** Stop the polling loop */
@@ -41,16 +126,124 @@ export class Poller {
       clearInterval(this.timer);
       this.timer = null;
     }
+    if (this.retryTimer) {
+      clearInterval(this.retryTimer);
+      this.retryTimer = null;
+    }
+  }
+
+  /**
+   * Heal downloads that failed after startup (e.g. a transient network
+   * error). Skips compounds whose download is still running.
+   */
+  private async retryDownloads(): Promise<void> {
+    if (this.retrying || this.client.circuitOpen) return;
+    const { state } = this.services;
+    const now = Date.now();
+    const refs = scanIncompleteDownloads(state.rootDir, state.data).filter((r) => {
+      if (isDownloadActive(r.compound_id)) return false;
+      const compound = state.findCompound(r.compound_id);
+      return compound !== undefined && downloadRetryDue(compound, now);
+    });
+    if (refs.length === 0) return;
+
+    this.retrying = true;
+    try {
+      await recoverIncompleteDownloads(this.services, this.client, refs);
+    } finally {
+      this.retrying = false;
+    }
+  }
+
+  /** Health snapshot for the UI */
+  getStatus(): PollerStatus {
+    return {
+      active_compounds: this.activeCompounds,
+      api_key_configured: this.services.state.activeApiKey() !== null,
+      last_tick_at: this.lastTickAt,
+      consecutive_errors: this.consecutiveErrors,
+    };
+  }
+
+  /**
+   * Resolve once no tick or download retry is running, or after timeoutMs.
+   * Resolves true if the poller drained in time. Call after stop() so no
+   * new work starts while waiting.
+   */
+  waitIdle(timeoutMs: number): Promise<boolean> {
+    if (this.inFlight === 0) return Promise.resolve(true);
+    return new Promise((resolve) => {
+      const onIdle = () => {
+        clearTimeout(timer);
+        resolve(true);
+      };
+      const timer = setTimeout(() => {
+        this.idleWaiters = this.idleWaiters.filter((w) => w !== onIdle);
+        resolve(false);
+      }, timeoutMs);
+      this.idleWaiters.push(onIdle);
+    });
+  }
+
+  /** Count a task as in flight until it settles */
+  private async track(task: Promise<void>): Promise<void> {
+    this.inFlight++;
+    try {
+      await task;
+    } finally {
+      this.inFlight--;
+      if (this.inFlight === 0) {
+        const waiters = this.idleWaiters;
+        this.idleWaiters = [];
+        for (const w of waiters) w();
+      }
+    }
+  }
+
+  setAutoArchiveDays(days: number | null): void {
+    this.autoArchiveDays = days;
   }
 
-  /** Single poll tick */
+  /** Single poll tick: compound polling, then the auto-archive pass */
   private async pollTick(): Promise<void> {
+    await this.pollCompounds();
+    this.autoArchiveRuns();
+  }
+
+  /** Archive completed runs older than the auto-archive threshold */
+  private autoArchiveRuns(): void {
+    if (this.autoArchiveDays === null) return;
+    const { state, eventBus } = this.services;
+    const cutoff = Date.now() - this.autoArchiveDays * 86_400_000;
+    const now = new Date().toISOString();
+
+    const archived: RunArchivedEvent[] = [];
+    for (const campaign of state.data.campaigns) {
+      for (const run of campaign.runs) {
+        if (run.archived || !run.completed_at) continue;
+        if (Date.parse(run.completed_at) > cutoff) continue;
+        run.archived = true;
+        run.archived_at = now;
+        state.audit('archive', 'run', run.id, `auto-archived after ${this.autoArchiveDays} days`);
+        archived.push({ run_id: run.id, campaign_id: campaign.id, archived_at: now });
+      }
+    }
+    if (archived.length === 0) return;
+
+    persistState(state.rootDir, state.data);
+    for (const event of archived) eventBus.emit('run-archived', event);
+  }
+
+  /** Check timeouts and poll in-progress compounds */
+  private async pollCompounds(): Promise<void> {
     const state = this.services.state;
-    const apiKey = state.data.api_key;
+    this.lastTickAt = new Date().toISOString();
+    const apiKey = state.activeApiKey();
     if (!apiKey) return;
 
     // Collect in-progress compounds
     let refs = state.allCompoundsInProgress();
+    this.activeCompounds = refs.length;
     if (refs.length === 0) return;
 
     // Check for timed-out compounds
@@ -60,7 +253,7 @@ export class Poller {
     refs = refs.filter((r) => {
       const submittedMs = new Date(r.submitted_at).getTime();
       const elapsed = now - submittedMs;
-      if (elapsed > POLL_TIMEOUT_MS) {
+      if (elapsed > timeoutMs(r)) {
         timedOut.push(r);
         return false;
       }
@@ -77,7 +270,7 @@ export class Poller {
         if (compound) {
           compound.status = 'TIMED_OUT';
           compound.completed_at = nowIso;
-          compound.error_message = 'Prediction timed out after 2 hours';
+          compound.error_message = `Prediction timed out after ${formatTimeout(timeoutMs(r))}`;
           state.markDirty();
         }
 
@@ -89,6 +282,7 @@ export class Poller {
           status: 'TIMED_OUT',
           metrics: null,
           completed_at: nowIso,
+          download_error: compound?.download_error ?? null,
         };
         this.services.eventBus.emit('compound-status-changed', statusEvent);
 
@@ -105,6 +299,29 @@ export class Poller {
 
     if (refs.length === 0) return;
 
+    if (this.client.circuitOpen) {
+      console.warn('Boltz API circuit breaker is open, skipping poll tick');
+      return;
+    }
+
+    // Adaptive backoff: long-running compounds are checked less often
+    const activeIds = new Set(refs.map((r) => r.compound_id));
+    for (const id of this.lastChecked.keys()) {
+      if (!activeIds.has(id)) this.lastChecked.delete(id);
+    }
+    refs = refs.filter((r) => {
+      const last = this.lastChecked.get(r.compound_id);
+      if (last === undefined) return true;
+      const elapsed = now - new Date(r.submitted_at).getTime();
+      // Slack so timer jitter doesn't push a compound to the next tick
+      return now - last + 1000 >= backoffIntervalMs(elapsed, this.intervalMs);
+    });
+    for (const r of refs) {
+      this.lastChecked.set(r.compound_id, now);
+    }
+
+    if (refs.length === 0) return;
+
     console.log(`Polling ${refs.length} in-progress compounds`);
 
     // Poll each compound with bounded concurrency
@@ -119,7 +336,10 @@ export class Poller {
     let prediction: PredictionStatus;
     try {
       prediction = await this.client.getPredictionStatus(apiKey, ref.boltz_job_id);
+      this.consecutiveErrors = 0;
     } catch (e) {
+      if (e instanceof CircuitOpenError) return; // opened mid-tick; already logged
+      this.consecutiveErrors++;
       const msg = e instanceof Error ? e.message : String(e);
       console.warn(`Failed to poll compound ${ref.compound_id}: ${msg}`);
       return;
@@ -165,6 +385,7 @@ export class Poller {
             status: newStatus,
             metrics: null,
             completed_at: null,
+            download_error: compound.download_error,
           };
           this.services.eventBus.emit('compound-status-changed', statusEvent);
         }
@@ -204,6 +425,7 @@ export class Poller {
       status: 'COMPLETED',
       metrics,
       completed_at: nowIso,
+      download_error: compound?.download_error ?? null,
     };
     this.services.eventBus.emit('compound-status-changed', statusEvent);
 
@@ -213,10 +435,20 @@ export class Poller {
     }
 
     // Spawn download task
-    const downloadUrl = prediction.prediction_results?.output?.download_url;
+    const output = prediction.prediction_results?.output;
+    const downloadUrl = output?.download_url;
     if (downloadUrl) {
       // Fire and forget -- errors are handled inside downloadAndStore
-      downloadAndStore(this.services, this.client, downloadUrl, ref).catch((err) => {
+      const checksum = output?.checksum ?? null;
+      const expectedSamples = state.findRun(ref.run_id)?.params.diffusion_samples ?? null;
+      downloadAndStore(
+        this.services,
+        this.client,
+        downloadUrl,
+        ref,
+        checksum,
+        expectedSamples,
+      ).catch((err) => {
         console.error(`Download failed for ${ref.compound_id}:`, err);
       });
     } else {
@@ -259,6 +491,7 @@ export class Poller {
       status,
       metrics: null,
       completed_at: nowIso,
+      download_error: compound?.download_error ?? null,
     };
     this.services.eventBus.emit('compound-status-changed', statusEvent);
 
@@ -272,7 +505,7 @@ export class Poller {
 // ── Startup Recovery ─────────────────────────────────────────────────
 
 /**
- * Recover incomplete downloads on startup.
+ * Recover incomplete downloads (on startup and from the periodic retry).
  * Re-polls for fresh download URLs and retries the download+store flow.
  */
 export async function recoverIncompleteDownloads(
@@ -284,7 +517,7 @@ export async function recoverIncompleteDownloads(
 
   console.log(`Recovering ${compounds.length} incomplete downloads`);
 
-  const apiKey = services.state.data.api_key;
+  const apiKey = services.state.activeApiKey();
   if (!apiKey) {
     console.warn('No API key configured, skipping download recovery');
     return;
@@ -293,9 +526,18 @@ export async function recoverIncompleteDownloads(
   for (const compoundRef of compounds) {
     try {
       const prediction = await client.getPredictionStatus(apiKey, compoundRef.boltz_job_id);
-      const downloadUrl = prediction.prediction_results?.output?.download_url;
-      if (downloadUrl) {
-        await downloadAndStore(services, client, downloadUrl, compoundRef);
+      const output = prediction.prediction_results?.output;
+      if (output?.download_url) {
+        const expectedSamples =
+          services.state.findRun(compoundRef.run_id)?.params.diffusion_samples ?? null;
+        await downloadAndStore(
+          services,
+          client,
+          output.download_url,
+          compoundRef,
+          output.checksum ?? null,
+          expectedSamples,
+        );
       }
     } catch (e) {
       const msg = e instanceof Error ? e.message : String(e);
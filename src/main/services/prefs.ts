@@ -2,10 +2,33 @@ import fs from 'node:fs';
 import path from 'node:path';
 import os from 'node:os';
 import { app } from 'electron';
+import type { RunParams, RunParamsPreset, WorkspaceEntry } from '../models/types';
+import {
+  DEFAULT_MAX_BACKUPS,
+  DEFAULT_RUN_PARAMS,
+  MAX_RECENT_WORKSPACES,
+  POLL_INTERVAL_MS,
+  SUBMIT_CONCURRENCY,
+} from '../models/types';
+import { atomicWriteJson } from './storage';
 
-interface Prefs {
+interface BackupConfig {
+  max_count: number;
+}
+
+export interface Prefs {
   root_dir: string;
   analytics_enabled?: boolean;
+  backup?: BackupConfig;
+  poll_interval_secs?: number;
+  submission_concurrency?: number;
+  compress_state?: boolean;
+  recent_workspaces?: WorkspaceEntry[];
+  run_param_presets?: RunParamsPreset[];
+  default_run_params_preset?: string | null;
+  external_viewer_path?: string | null;
+  proxy_url?: string | null;
+  auto_archive_completed_runs_after_days?: number | null;
 }
 
 function prefsPath(): string {
@@ -16,48 +39,152 @@ export function defaultRootDir(): string {
   return path.join(os.homedir(), 'multiplexer');
 }
 
-export function readRootDir(): string {
-  if (process.env.MULTIPLEXER_ROOT_DIR) return process.env.MULTIPLEXER_ROOT_DIR;
-  const p = prefsPath();
-  if (!fs.existsSync(p)) {
-    return defaultRootDir();
-  }
-  try {
-    const raw = fs.readFileSync(p, 'utf-8');
-    const prefs: Prefs = JSON.parse(raw);
-    return prefs.root_dir || defaultRootDir();
-  } catch {
-    return defaultRootDir();
-  }
+export function readAnalyticsEnabled(): boolean {
+  return readAllPrefs().analytics_enabled ?? true;
 }
 
-export function writeRootDir(rootDir: string): void {
-  const existing = readPrefs();
-  const prefs: Prefs = { ...existing, root_dir: rootDir };
-  writePrefs(prefs);
+export function writeAnalyticsEnabled(enabled: boolean): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, analytics_enabled: enabled });
 }
 
-export function readAnalyticsEnabled(): boolean {
-  return readPrefs().analytics_enabled ?? true;
+export function readBackupMaxCount(): number {
+  return readAllPrefs().backup?.max_count ?? DEFAULT_MAX_BACKUPS;
 }
 
-export function writeAnalyticsEnabled(enabled: boolean): void {
-  const existing = readPrefs();
-  writePrefs({ ...existing, analytics_enabled: enabled });
+export function readPollIntervalSecs(): number {
+  return readAllPrefs().poll_interval_secs ?? POLL_INTERVAL_MS / 1000;
+}
+
+export function writePollIntervalSecs(secs: number): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, poll_interval_secs: secs });
+}
+
+export function readSubmissionConcurrency(): number {
+  return readAllPrefs().submission_concurrency ?? SUBMIT_CONCURRENCY;
+}
+
+export function writeSubmissionConcurrency(concurrency: number): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, submission_concurrency: concurrency });
+}
+
+export function readCompressState(): boolean {
+  return readAllPrefs().compress_state ?? false;
+}
+
+export function writeCompressState(enabled: boolean): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, compress_state: enabled });
+}
+
+/** Most recently used workspaces, newest first. */
+export function readRecentWorkspaces(): WorkspaceEntry[] {
+  return readAllPrefs().recent_workspaces ?? [];
+}
+
+/** Move rootDir to the front of the MRU list. */
+export function touchRecentWorkspace(rootDir: string): void {
+  const existing = readAllPrefs();
+  const others = (existing.recent_workspaces ?? []).filter((w) => w.path !== rootDir);
+  const recent = [{ path: rootDir, last_used: new Date().toISOString() }, ...others];
+  writeAllPrefs({ ...existing, recent_workspaces: recent.slice(0, MAX_RECENT_WORKSPACES) });
+}
+
+export function readRunParamsPresets(): RunParamsPreset[] {
+  return readAllPrefs().run_param_presets ?? [];
+}
+
+/** Save a preset, overwriting any existing preset with the same name. */
+export function saveRunParamsPreset(preset: RunParamsPreset): void {
+  const existing = readAllPrefs();
+  const presets = (existing.run_param_presets ?? []).filter((p) => p.name !== preset.name);
+  writeAllPrefs({ ...existing, run_param_presets: [...presets, preset] });
+}
+
+/** Delete a preset. Clears the default if it pointed at this preset. */
+export function deleteRunParamsPreset(name: string): void {
+  const existing = readAllPrefs();
+  const presets = (existing.run_param_presets ?? []).filter((p) => p.name !== name);
+  const defaultName =
+    existing.default_run_params_preset === name ? null : existing.default_run_params_preset;
+  writeAllPrefs({ ...existing, run_param_presets: presets, default_run_params_preset: defaultName });
+}
+
+export function writeDefaultRunParamsPreset(name: string | null): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, default_run_params_preset: name });
+}
+
+/** Params of the default preset, or DEFAULT_RUN_PARAMS when none is set. */
+export function readDefaultRunParams(): RunParams {
+  const prefs = readAllPrefs();
+  const name = prefs.default_run_params_preset;
+  const preset = name ? prefs.run_param_presets?.find((p) => p.name === name) : undefined;
+  // Presets saved before a field existed pick up its default
+  return preset ? { ...DEFAULT_RUN_PARAMS, ...preset.params } : { ...DEFAULT_RUN_PARAMS };
+}
+
+/** Structure viewer to launch instead of the OS default, if configured. */
+export function readExternalViewerPath(): string | null {
+  return readAllPrefs().external_viewer_path ?? null;
+}
+
+export function writeExternalViewerPath(viewerPath: string | null): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, external_viewer_path: viewerPath });
 }
 
-function readPrefs(): Prefs {
+export function readProxyUrl(): string | null {
+  return readAllPrefs().proxy_url ?? null;
+}
+
+export function writeProxyUrl(proxyUrl: string | null): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, proxy_url: proxyUrl });
+}
+
+/** Days after completion before the poller archives a run; null disables it. */
+export function readAutoArchiveDays(): number | null {
+  return readAllPrefs().auto_archive_completed_runs_after_days ?? null;
+}
+
+export function writeAutoArchiveDays(days: number | null): void {
+  const existing = readAllPrefs();
+  writeAllPrefs({ ...existing, auto_archive_completed_runs_after_days: days });
+}
+
+/** Proxy for Boltz API requests: HTTPS_PROXY / https_proxy, then prefs. */
+export function resolveProxyUrl(): string | null {
+  return process.env.HTTPS_PROXY || process.env.https_proxy || readProxyUrl();
+}
+
+// In-memory copy of prefs.json, loaded on first read and kept in sync by
+// writeAllPrefs, so settings reads don't hit the disk every time
+let cachedPrefs: Prefs | null = null;
+
+function loadPrefs(): Prefs {
   const p = prefsPath();
   if (!fs.existsSync(p)) return { root_dir: defaultRootDir() };
   try {
-    return JSON.parse(fs.readFileSync(p, 'utf-8'));
+    const prefs: Prefs = JSON.parse(fs.readFileSync(p, 'utf-8'));
+    return { ...prefs, root_dir: prefs.root_dir || defaultRootDir() };
   } catch {
     return { root_dir: defaultRootDir() };
   }
 }
 
-function writePrefs(prefs: Prefs): void {
+/** All preferences; fields missing from prefs.json are left undefined except root_dir. */
+export function readAllPrefs(): Prefs {
+  if (!cachedPrefs) cachedPrefs = loadPrefs();
+  return { ...cachedPrefs };
+}
+
+/** Replace prefs.json (atomically) and the in-memory copy. */
+export function writeAllPrefs(prefs: Prefs): void {
   const p = prefsPath();
   fs.mkdirSync(path.dirname(p), { recursive: true });
-  fs.writeFileSync(p, JSON.stringify(prefs, null, 2), 'utf-8');
+  atomicWriteJson(p, prefs);
+  cachedPrefs = { ...prefs };
 }
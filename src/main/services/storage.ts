@@ -1,91 +1,527 @@
 import fs from 'node:fs';
 import path from 'node:path';
-import type { AppData, CompoundRef } from '../models/types';
-import { AppState } from '../models/state';
-import { FLUSH_INTERVAL_MS } from '../models/types';
+import zlib from 'node:zlib';
+import { v4 as uuidv4 } from 'uuid';
+import type {
+  AppData,
+  BackupInfo,
+  CompoundRef,
+  OrphanedPath,
+  WorkspaceReport,
+  WorkspaceSize,
+} from '../models/types';
+import {
+  AppState,
+  checkIntegrity,
+  computeInputHash,
+  findCampaignByFolder,
+  findRunByFolder,
+  singleLigand,
+} from '../models/state';
+import {
+  CURRENT_SCHEMA_VERSION,
+  BACKUP_MIN_INTERVAL_MS,
+  DEFAULT_MAX_BACKUPS,
+  FLUSH_INTERVAL_MS,
+} from '../models/types';
 
 // ── Load / Persist ──────────────────────────────────────────────────
 
-/** Load state from {rootDir}/state.json, creating defaults if missing. */
+let compressState = false;
+
+/** Toggle gzip-compressed state.json.gz writes (from prefs). */
+export function configureCompression(enabled: boolean): void {
+  compressState = enabled;
+}
+
+/**
+ * Path of the state file to load: state.json or state.json.gz, whichever
+ * was written most recently. Null when neither exists.
+ */
+function currentStatePath(rootDir: string): string | null {
+  const candidates = ['state.json', 'state.json.gz']
+    .map((name) => path.join(rootDir, name))
+    .filter((p) => fs.existsSync(p));
+  if (candidates.length === 0) return null;
+
+  return candidates.reduce((newest, p) =>
+    fs.statSync(p).mtimeMs > fs.statSync(newest).mtimeMs ? p : newest,
+  );
+}
+
+/** Read and parse a state (or backup) file, gunzipping if it has the gzip magic bytes. */
+function readStateFile(filePath: string): AppData {
+  let bytes = fs.readFileSync(filePath);
+  if (bytes.length >= 2 && bytes[0] === 0x1f && bytes[1] === 0x8b) {
+    bytes = zlib.gunzipSync(bytes);
+  }
+  return JSON.parse(bytes.toString('utf-8'));
+}
+
+/**
+ * Read and migrate the workspace's current state file without touching the
+ * lock or backups. Throws if there is no state file.
+ */
+export function readStateFromDisk(rootDir: string): { data: AppData; migrated: boolean } {
+  const statePath = currentStatePath(rootDir);
+  if (!statePath) throw new Error(`No state file in ${rootDir}`);
+  const loaded = readStateFile(statePath);
+  const loadedVersion = loaded.schema_version;
+  const data = migrateAppData(loaded);
+  return { data, migrated: data.schema_version !== loadedVersion };
+}
+
+/**
+ * Load state from {rootDir}/state.json(.gz), creating defaults if missing.
+ * Takes the workspace lock first, so this throws if another instance owns it.
+ */
 export function loadState(rootDir: string): AppState {
   fs.mkdirSync(rootDir, { recursive: true });
+  acquireWorkspaceLock(rootDir);
 
-  const statePath = path.join(rootDir, 'state.json');
-  const backupPath = path.join(rootDir, 'state.json.bak');
-
-  if (!fs.existsSync(statePath)) {
+  const statePath = currentStatePath(rootDir);
+  if (!statePath) {
     return new AppState(AppState.defaultData(), rootDir);
   }
 
-  const raw = fs.readFileSync(statePath, 'utf-8');
-  const data: AppData = JSON.parse(raw);
+  const loaded = readStateFile(statePath);
+  const loadedVersion = loaded.schema_version;
+  const data = migrateAppData(loaded);
+  const migrated = data.schema_version !== loadedVersion;
+
+  // Create backup for crash recovery
+  backupState(rootDir);
+
+  const state = new AppState(data, rootDir);
+  if (migrated) state.markDirty();
+  return state;
+}
+
+/**
+ * Atomic write: serialize → .state.json.tmp → rename.
+ * With compression enabled: gzip → .state.json.gz.tmp → state.json.gz.
+ * Crash-safe on APFS (same-volume rename is atomic).
+ * Integrity problems are logged before writing.
+ */
+export function persistState(rootDir: string, data: AppData): void {
+  const fileName = compressState ? 'state.json.gz' : 'state.json';
+
+  // Warn but still write: refusing to save would lose more than it protects
+  for (const warning of checkIntegrity(data)) {
+    console.warn(`State integrity: ${warning}`);
+  }
+
+  atomicWriteJsonWithBackup(path.join(rootDir, fileName), data, { gzip: compressState });
+}
+
+/**
+ * Serialize data as pretty-printed JSON (optionally gzipped) and write it
+ * via a hidden .{name}.tmp sibling plus rename, so readers never see a
+ * partial file.
+ */
+export function atomicWriteJson(
+  filePath: string,
+  data: unknown,
+  options: { gzip?: boolean } = {},
+): void {
+  const tmpPath = path.join(path.dirname(filePath), `.${path.basename(filePath)}.tmp`);
+  const json = JSON.stringify(data, null, 2);
+  if (options.gzip) {
+    fs.writeFileSync(tmpPath, zlib.gzipSync(json));
+  } else {
+    fs.writeFileSync(tmpPath, json, 'utf-8');
+  }
+  fs.renameSync(tmpPath, filePath);
+}
+
+/**
+ * atomicWriteJson after rotating the state backups (state.json.bak*) in the
+ * same directory. Backups rotate at most once per BACKUP_MIN_INTERVAL_MS so
+ * a burst of saves doesn't push every useful snapshot out; the tmp+rename
+ * already keeps each individual write safe.
+ */
+export function atomicWriteJsonWithBackup(
+  filePath: string,
+  data: unknown,
+  options: { gzip?: boolean } = {},
+): void {
+  const rootDir = path.dirname(filePath);
+  const last = lastBackupAt.get(rootDir) ?? 0;
+  if (Date.now() - last >= BACKUP_MIN_INTERVAL_MS) backupState(rootDir);
+  atomicWriteJson(filePath, data, options);
+}
+
+// ── Workspace Lock ──────────────────────────────────────────────────
+
+const LOCK_FILE = '.multiplexer.lock';
+
+function isProcessAlive(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (e) {
+    // EPERM: process exists but belongs to another user
+    return (e as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/**
+ * Write {rootDir}/.multiplexer.lock with our PID. Throws if a live process
+ * already holds it; a stale lock left by a crash is replaced.
+ */
+export function acquireWorkspaceLock(rootDir: string): void {
+  const lockPath = path.join(rootDir, LOCK_FILE);
+
+  if (fs.existsSync(lockPath)) {
+    const pid = Number(fs.readFileSync(lockPath, 'utf-8').trim());
+    if (pid === process.pid) return;
+    if (Number.isInteger(pid) && pid > 0 && isProcessAlive(pid)) {
+      throw new Error(
+        `Workspace ${rootDir} is already open in another Multiplexer window (PID ${pid}).`,
+      );
+    }
+    console.warn(`Removing stale workspace lock (PID ${pid})`);
+  }
+
+  fs.writeFileSync(lockPath, String(process.pid), 'utf-8');
+}
+
+/** Remove the lock file if we own it. */
+export function releaseWorkspaceLock(rootDir: string): void {
+  const lockPath = path.join(rootDir, LOCK_FILE);
+  try {
+    const pid = Number(fs.readFileSync(lockPath, 'utf-8').trim());
+    if (pid === process.pid) fs.rmSync(lockPath, { force: true });
+  } catch {
+    // Already gone
+  }
+}
+
+// ── Backups ─────────────────────────────────────────────────────────
+
+let maxBackups = DEFAULT_MAX_BACKUPS;
+const lastBackupAt = new Map<string, number>(); // rootDir → epoch ms
+
+/** Set how many rolling state.json.bak* files to keep (from prefs). */
+export function configureBackups(maxCount: number): void {
+  maxBackups = Math.max(1, Math.floor(maxCount));
+}
+
+function backupFileName(index: number): string {
+  return index === 0 ? 'state.json.bak' : `state.json.bak.${index}`;
+}
+
+/**
+ * Shift existing backups up by one slot (.bak → .bak.1 → .bak.2 …),
+ * dropping the oldest so at most maxCount files remain.
+ */
+export function rotateBackups(rootDir: string, maxCount: number): void {
+  const oldest = path.join(rootDir, backupFileName(maxCount - 1));
+  if (fs.existsSync(oldest)) {
+    fs.rmSync(oldest, { force: true });
+  }
+
+  for (let i = maxCount - 2; i >= 0; i--) {
+    const from = path.join(rootDir, backupFileName(i));
+    if (fs.existsSync(from)) {
+      fs.renameSync(from, path.join(rootDir, backupFileName(i + 1)));
+    }
+  }
+}
+
+/**
+ * Rotate backups and copy the current state file into state.json.bak.
+ * A compressed state file produces a compressed backup (same name).
+ */
+function backupState(rootDir: string): void {
+  const statePath = currentStatePath(rootDir);
+  if (!statePath) return;
+
+  try {
+    rotateBackups(rootDir, maxBackups);
+    fs.copyFileSync(statePath, path.join(rootDir, backupFileName(0)));
+    lastBackupAt.set(rootDir, Date.now());
+  } catch (e) {
+    // Non-fatal — backup creation is best-effort
+    console.warn('Failed to back up state.json:', e);
+  }
+}
+
+/** List rolling backups, newest first. */
+export function listBackups(rootDir: string): BackupInfo[] {
+  if (!fs.existsSync(rootDir)) return [];
+
+  const backups: { index: number; info: BackupInfo }[] = [];
+  for (const name of fs.readdirSync(rootDir)) {
+    const match = name.match(/^state\.json\.bak(?:\.(\d+))?$/);
+    if (!match) continue;
+
+    const stat = fs.statSync(path.join(rootDir, name));
+    backups.push({
+      index: match[1] ? Number(match[1]) : 0,
+      info: {
+        name,
+        size_bytes: stat.size,
+        modified_at: stat.mtime.toISOString(),
+      },
+    });
+  }
+
+  return backups.sort((a, b) => a.index - b.index).map((b) => b.info);
+}
+
+/**
+ * Read and migrate a backup file. The name must match one of the files
+ * returned by listBackups — anything else is rejected to prevent path traversal.
+ */
+export function readBackup(rootDir: string, backupName: string): AppData {
+  const known = listBackups(rootDir).map((b) => b.name);
+  if (!known.includes(backupName)) {
+    throw new Error(`Unknown backup: "${backupName}"`);
+  }
 
-  if (data.schema_version > 3) {
+  return migrateAppData(readStateFile(path.join(rootDir, backupName)));
+}
+
+// ── Schema Migration ────────────────────────────────────────────────
+
+/**
+ * Upgrade loaded AppData to CURRENT_SCHEMA_VERSION by applying each
+ * migration step in order. Throws if the file was written by a newer app.
+ */
+export function migrateAppData(data: AppData): AppData {
+  if (data.schema_version > CURRENT_SCHEMA_VERSION) {
     throw new Error(
       `Unsupported state schema version: ${data.schema_version}. Please update Multiplexer.`,
     );
   }
 
-  // Migrate v1 → v2: null out placeholder zero-affinity metrics
-  let migrated = false;
-  if (!data.schema_version || data.schema_version < 2) {
-    for (const campaign of data.campaigns) {
-      for (const run of campaign.runs) {
-        for (const compound of run.compounds) {
-          const aff = compound.metrics?.affinity;
-          if (
-            aff &&
-            aff.binding_confidence === 0 &&
-            aff.optimization_score === 0
-          ) {
-            compound.metrics!.affinity = null;
-          }
+  if (!data.schema_version || data.schema_version < 2) migrateV1ToV2(data);
+  if (data.schema_version < 3) migrateV2ToV3(data);
+  if (data.schema_version < 4) migrateV3ToV4(data);
+  if (data.schema_version < 5) migrateV4ToV5(data);
+  if (data.schema_version < 6) migrateV5ToV6(data);
+  if (data.schema_version < 7) migrateV6ToV7(data);
+  if (data.schema_version < 8) migrateV7ToV8(data);
+  if (data.schema_version < 9) migrateV8ToV9(data);
+  if (data.schema_version < 10) migrateV9ToV10(data);
+  if (data.schema_version < 11) migrateV10ToV11(data);
+  if (data.schema_version < 12) migrateV11ToV12(data);
+  if (data.schema_version < 13) migrateV12ToV13(data);
+  if (data.schema_version < 14) migrateV13ToV14(data);
+  if (data.schema_version < 15) migrateV14ToV15(data);
+  if (data.schema_version < 16) migrateV15ToV16(data);
+  if (data.schema_version < 17) migrateV16ToV17(data);
+  if (data.schema_version < 18) migrateV17ToV18(data);
+
+  return data;
+}
+
+/** v1 → v2: null out placeholder zero-affinity metrics, default download_error */
+function migrateV1ToV2(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.download_error ??= null;
+        const aff = compound.metrics?.affinity;
+        if (
+          aff &&
+          aff.binding_confidence === 0 &&
+          aff.optimization_score === 0
+        ) {
+          compound.metrics!.affinity = null;
         }
       }
     }
-    data.schema_version = 2;
-    migrated = true;
   }
+  data.schema_version = 2;
+}
+
+/** v2 → v3: add target_type, rename protein_sequence → target_sequence */
+function migrateV2ToV3(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    const c = campaign as unknown as Record<string, unknown>;
+    if (!c.target_type) {
+      c.target_type = 'protein';
+    }
+    if ('protein_sequence' in c && !('target_sequence' in c)) {
+      c.target_sequence = c.protein_sequence;
+      delete c.protein_sequence;
+    }
+  }
+  data.schema_version = 3;
+}
 
-  // Migrate v2 → v3: add target_type, rename protein_sequence → target_sequence
-  if (data.schema_version < 3) {
-    for (const campaign of data.campaigns) {
-      const c = campaign as Record<string, unknown>;
-      if (!c.target_type) {
-        c.target_type = 'protein';
+/** v3 → v4: add user notes to campaigns, runs, and compounds */
+function migrateV3ToV4(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    campaign.note ??= null;
+    for (const run of campaign.runs) {
+      run.note ??= null;
+      for (const compound of run.compounds) {
+        compound.note ??= null;
       }
-      if ('protein_sequence' in c && !('target_sequence' in c)) {
-        c.target_sequence = c.protein_sequence;
-        delete c.protein_sequence;
+    }
+  }
+  data.schema_version = 4;
+}
+
+/** v4 → v5: add download/CIF checksums to compounds */
+function migrateV4ToV5(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.download_checksum ??= null;
+        compound.cif_checksum ??= null;
       }
     }
-    data.schema_version = 3;
-    migrated = true;
   }
+  data.schema_version = 5;
+}
 
-  // Create backup for crash recovery
-  try {
-    fs.copyFileSync(statePath, backupPath);
-  } catch {
-    // Non-fatal — backup creation is best-effort
+/** v5 → v6: COMPLETED compounds with a download_error become DOWNLOAD_FAILED */
+function migrateV5ToV6(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        if (compound.status === 'COMPLETED' && compound.download_error) {
+          compound.status = 'DOWNLOAD_FAILED';
+        }
+      }
+    }
   }
+  data.schema_version = 6;
+}
 
-  const state = new AppState(data, rootDir);
-  if (migrated) state.markDirty();
-  return state;
+/** v6 → v7: add Run.paused */
+function migrateV6ToV7(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      run.paused ??= false;
+    }
+  }
+  data.schema_version = 7;
 }
 
-/**
- * Atomic write: serialize → .state.json.tmp → rename.
- * Crash-safe on APFS (same-volume rename is atomic).
- */
-export function persistState(rootDir: string, data: AppData): void {
-  const statePath = path.join(rootDir, 'state.json');
-  const tmpPath = path.join(rootDir, '.state.json.tmp');
+/** v7 → v8: add Compound.input_hash, computed from the existing inputs */
+function migrateV7ToV8(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.input_hash ??= computeInputHash(
+          campaign.target_type,
+          campaign.target_sequence,
+          singleLigand(compound.smiles),
+          run.params,
+        );
+      }
+    }
+  }
+  data.schema_version = 8;
+}
 
-  const json = JSON.stringify(data, null, 2);
-  fs.writeFileSync(tmpPath, json, 'utf-8');
-  fs.renameSync(tmpPath, statePath);
+/** v8 → v9: add RunParams.timeout_secs (null = global timeout) */
+function migrateV8ToV9(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      run.params.timeout_secs ??= null;
+    }
+  }
+  data.schema_version = 9;
+}
+
+/** v9 → v10: add AppData.audit_log (an existing log is kept as-is) */
+function migrateV9ToV10(data: AppData): void {
+  data.audit_log ??= [];
+  data.schema_version = 10;
+}
+
+/** v10 → v11: add Compound.archived / archived_at */
+function migrateV10ToV11(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.archived ??= false;
+        compound.archived_at ??= null;
+      }
+    }
+  }
+  data.schema_version = 11;
+}
+
+/** v11 → v12: lift the single api_key into api_keys as "Default" */
+function migrateV11ToV12(data: AppData): void {
+  const legacy = data as AppData & { api_key?: string | null };
+  if (!data.api_keys) {
+    data.api_keys = legacy.api_key
+      ? [{ id: uuidv4(), label: 'Default', key: legacy.api_key, is_default: true }]
+      : [];
+  }
+  data.active_api_key_id ??= data.api_keys[0]?.id ?? null;
+  delete legacy.api_key;
+  data.schema_version = 12;
+}
+
+/** v12 → v13: add Run.compacted */
+function migrateV12ToV13(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      run.compacted ??= null;
+    }
+  }
+  data.schema_version = 13;
+}
+
+/** v13 → v14: lift Compound.smiles into smiles_list on the default ligand chain */
+function migrateV13ToV14(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.smiles_list ??= singleLigand(compound.smiles);
+      }
+    }
+  }
+  data.schema_version = 14;
+}
+
+/** v14 → v15: add RunParams.mode (every earlier run had a ligand) */
+function migrateV14ToV15(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      run.params.mode ??= 'protein_ligand';
+    }
+  }
+  data.schema_version = 15;
+}
+
+/** v15 → v16: add AppData.templates */
+function migrateV15ToV16(data: AppData): void {
+  data.templates ??= [];
+  data.schema_version = 16;
+}
+
+/** v16 → v17: add display_order to campaigns and runs, keeping the stored order */
+function migrateV16ToV17(data: AppData): void {
+  data.campaigns.forEach((campaign, i) => {
+    campaign.display_order ??= i;
+    campaign.runs.forEach((run, j) => {
+      run.display_order ??= j;
+    });
+  });
+  data.schema_version = 17;
+}
+
+/** v17 → v18: add download retry bookkeeping to compounds */
+function migrateV17ToV18(data: AppData): void {
+  for (const campaign of data.campaigns) {
+    for (const run of campaign.runs) {
+      for (const compound of run.compounds) {
+        compound.download_attempts ??= 0;
+        compound.last_download_attempt_at ??= null;
+      }
+    }
+  }
+  data.schema_version = 18;
 }
 
 // ── Dirty-Flag Flusher ──────────────────────────────────────────────
@@ -107,6 +543,59 @@ export function startPersistenceFlusher(state: AppState): () => void {
   return () => clearInterval(timer);
 }
 
+// ── Disk Usage ──────────────────────────────────────────────────────
+
+const COMPOUND_OUTPUT_RE = /^sample_\d+_(structure\.cif|pae\.png)$/;
+
+/**
+ * Sum file sizes under rootDir. Uses async fs calls so walking a large
+ * workspace doesn't block the main process. Symlinks are not followed.
+ */
+export async function computeWorkspaceSize(rootDir: string): Promise<WorkspaceSize> {
+  const size: WorkspaceSize = {
+    total_bytes: 0,
+    compound_output_bytes: 0,
+    state_json_bytes: 0,
+    temp_bytes: 0,
+  };
+  const tempDir = path.join(rootDir, '.boltz-temp');
+
+  async function walk(dir: string, inTemp: boolean): Promise<void> {
+    let entries: fs.Dirent[];
+    try {
+      entries = await fs.promises.readdir(dir, { withFileTypes: true });
+    } catch {
+      return; // removed mid-walk or unreadable
+    }
+    for (const entry of entries) {
+      const entryPath = path.join(dir, entry.name);
+      if (entry.isDirectory()) {
+        await walk(entryPath, inTemp || entryPath === tempDir);
+        continue;
+      }
+      if (!entry.isFile()) continue;
+
+      let bytes: number;
+      try {
+        bytes = (await fs.promises.stat(entryPath)).size;
+      } catch {
+        continue;
+      }
+      size.total_bytes += bytes;
+      if (inTemp) {
+        size.temp_bytes += bytes;
+      } else if (dir === rootDir && (entry.name === 'state.json' || entry.name === 'state.json.gz')) {
+        size.state_json_bytes += bytes;
+      } else if (COMPOUND_OUTPUT_RE.test(entry.name)) {
+        size.compound_output_bytes += bytes;
+      }
+    }
+  }
+
+  await walk(rootDir, false);
+  return size;
+}
+
 // ── Folder Operations ───────────────────────────────────────────────
 
 export function createCampaignFolder(
@@ -153,6 +642,27 @@ export function cleanupTempDir(rootDir: string): void {
   }
 }
 
+/**
+ * Show a path in the OS file manager (Finder, Explorer, or the desktop's
+ * default on Linux). With reveal, opens the parent with the path selected;
+ * otherwise opens the folder itself.
+ */
+export async function openPathInFileManager(targetPath: string, reveal: boolean): Promise<void> {
+  // showItemInFolder is cross-platform but fails silently on missing paths
+  if (!fs.existsSync(targetPath)) {
+    throw new Error(`Folder not found: ${targetPath}`);
+  }
+  // Imported here so storage stays loadable outside Electron (unit tests)
+  const { shell } = await import('electron');
+  if (reveal) {
+    shell.showItemInFolder(targetPath);
+    return;
+  }
+  // openPath resolves to an error message, or '' on success
+  const error = await shell.openPath(targetPath);
+  if (error) throw new Error(`Failed to open folder: ${error}`);
+}
+
 // ── Path Resolution ─────────────────────────────────────────────────
 
 export function resolveCompoundPath(
@@ -172,23 +682,51 @@ export function resolveCompoundPath(
 
 // ── Validation ──────────────────────────────────────────────────────
 
-function validateFolderName(name: string): void {
+export function validateFolderName(name: string): void {
   if (!name || name.includes('..') || name.includes('/') || name.includes('\\')) {
     throw new Error(`Invalid folder name: "${name}"`);
   }
 }
 
+/** Letters NFKD doesn't decompose into ASCII, transliterated by hand */
+const TRANSLITERATIONS: Record<string, string> = {
+  ß: 'ss',
+  æ: 'ae',
+  œ: 'oe',
+  ø: 'o',
+  đ: 'd',
+  ð: 'd',
+  ł: 'l',
+  þ: 'th',
+  ı: 'i',
+};
+
+/**
+ * Map accented letters to ASCII (é → e, ü → u, ß → ss) and emoji to their
+ * code point (🧬 → u1f9ec). Other scripts are left for the '-' fallback.
+ */
+function asciiFold(name: string): string {
+  return name
+    .replace(/[\u200D\uFE0F]/g, '') // joiners and emoji presentation selectors
+    .replace(/\p{Extended_Pictographic}/gu, (e) => `-u${e.codePointAt(0)!.toString(16)}-`)
+    .normalize('NFKD')
+    .replace(/\p{M}/gu, '')
+    .replace(/[^\x00-\x7F]/g, (ch) => TRANSLITERATIONS[ch] ?? ch);
+}
+
 /**
  * Sanitise user-provided name into filesystem-safe folder name.
  * - Converts to lowercase
- * - Replaces non-alphanumeric chars with '-'
+ * - Folds accented letters and emoji to ASCII (see asciiFold)
+ * - Replaces remaining non-alphanumeric chars with '-'
  * - Trims trailing '-'
  * - Truncates to ~200 bytes (UTF-8 safe)
  * - Returns "unnamed" if empty
+ *
+ * Only affects new folders; existing folder names are stored in state.
  */
 export function sanitiseFolderName(name: string): string {
-  let result = name
-    .toLowerCase()
+  let result = asciiFold(name.toLowerCase())
     .replace(/[^a-z0-9]+/g, '-')
     .replace(/-+$/, '')
     .replace(/^-+/, '');
@@ -218,10 +756,152 @@ export function uniqueFolderName(
   return `${baseName}-${counter}`;
 }
 
+// ── Workspace Validation ────────────────────────────────────────────
+
+/**
+ * Cross-check the state tree against disk: every campaign and run needs a
+ * folder, and every COMPLETED compound needs its sample_0 CIF. Read-only.
+ */
+export function validateWorkspace(rootDir: string, data: AppData): WorkspaceReport {
+  const report: WorkspaceReport = {
+    missing_campaign_folders: [],
+    missing_run_folders: [],
+    missing_compound_outputs: [],
+  };
+
+  for (const campaign of data.campaigns) {
+    const campaignDir = path.join(rootDir, campaign.folder_name);
+    if (!fs.existsSync(campaignDir)) {
+      report.missing_campaign_folders.push({
+        display_name: campaign.display_name,
+        path: campaignDir,
+      });
+    }
+
+    for (const run of campaign.runs) {
+      const runDir = path.join(campaignDir, run.folder_name);
+      if (!fs.existsSync(runDir)) {
+        report.missing_run_folders.push({
+          display_name: run.display_name,
+          path: runDir,
+        });
+      }
+
+      for (const compound of run.compounds) {
+        if (compound.status !== 'COMPLETED') continue;
+
+        const cifPath = path.join(runDir, compound.folder_name, 'sample_0_structure.cif');
+        if (!fs.existsSync(cifPath)) {
+          report.missing_compound_outputs.push({
+            display_name: compound.display_name,
+            path: cifPath,
+          });
+        }
+      }
+    }
+  }
+
+  return report;
+}
+
+/** Total bytes of regular files under dir. Symlinks are not followed. */
+async function directorySize(dir: string): Promise<number> {
+  let total = 0;
+  for (const entry of await fs.promises.readdir(dir, { withFileTypes: true })) {
+    const entryPath = path.join(dir, entry.name);
+    if (entry.isDirectory()) {
+      total += await directorySize(entryPath);
+    } else if (entry.isFile()) {
+      total += (await fs.promises.stat(entryPath)).size;
+    }
+  }
+  return total;
+}
+
+/** Non-hidden folders directly under dir, so .boltz-temp is skipped */
+function visibleSubfolders(dir: string): string[] {
+  try {
+    return fs
+      .readdirSync(dir, { withFileTypes: true })
+      .filter((e) => e.isDirectory() && !e.name.startsWith('.'))
+      .map((e) => e.name);
+  } catch {
+    return [];
+  }
+}
+
+/**
+ * Campaign-level and run-level folders on disk that no campaign or run in
+ * data points at — typically left behind by a crash mid-create or by files
+ * copied in by hand. An orphaned campaign folder is reported once, without
+ * listing the runs inside it. Hidden folders are skipped.
+ */
+export async function scanOrphanedFolders(rootDir: string, data: AppData): Promise<OrphanedPath[]> {
+  const orphans: string[] = [];
+
+  for (const campaignFolder of visibleSubfolders(rootDir)) {
+    if (!findCampaignByFolder(data, campaignFolder)) {
+      orphans.push(path.join(rootDir, campaignFolder));
+      continue;
+    }
+    for (const runFolder of visibleSubfolders(path.join(rootDir, campaignFolder))) {
+      if (!findRunByFolder(data, campaignFolder, runFolder)) {
+        orphans.push(path.join(rootDir, campaignFolder, runFolder));
+      }
+    }
+  }
+
+  const result: OrphanedPath[] = [];
+  for (const orphan of orphans) {
+    let sizeBytes: number | null;
+    try {
+      sizeBytes = await directorySize(orphan);
+    } catch {
+      sizeBytes = null; // removed mid-walk or unreadable
+    }
+    result.push({ path: orphan, size_bytes: sizeBytes });
+  }
+  return result;
+}
+
+/**
+ * Delete a folder reported by scanOrphanedFolders. Re-checks against data
+ * first, so a path that has since been claimed by a campaign or run (or
+ * that points anywhere else) is refused.
+ */
+export async function deleteOrphanedFolder(
+  rootDir: string,
+  data: AppData,
+  folderPath: string,
+): Promise<void> {
+  const resolved = path.resolve(folderPath);
+  const orphans = await scanOrphanedFolders(rootDir, data);
+  if (!orphans.some((o) => path.resolve(o.path) === resolved)) {
+    throw new Error(`Not an orphaned folder: ${folderPath}`);
+  }
+  await fs.promises.rm(resolved, { recursive: true, force: true });
+}
+
+// ── Disk Space ──────────────────────────────────────────────────────
+
+const BYTES_PER_MB = 1024 * 1024;
+
+/** Throw if the volume holding rootDir has fewer than requiredBytes free. */
+export function checkAvailableSpace(rootDir: string, requiredBytes: number): void {
+  const stats = fs.statfsSync(rootDir);
+  const available = stats.bavail * stats.bsize;
+  if (available < requiredBytes) {
+    const need = Math.ceil(requiredBytes / BYTES_PER_MB);
+    const have = Math.floor(available / BYTES_PER_MB);
+    throw new Error(`Insufficient disk space: need ${need}MB, have ${have}MB`);
+  }
+}
+
 // ── Download Recovery ───────────────────────────────────────────────
 
 /**
- * Find COMPLETED compounds missing their CIF files on disk.
+ * Find compounds whose outputs need downloading: DOWNLOAD_FAILED ones,
+ * plus COMPLETED ones missing their CIF files on disk.
  * Used on startup before poller begins.
  */
 export function scanIncompleteDownloads(
@@ -233,7 +913,7 @@ export function scanIncompleteDownloads(
   for (const campaign of data.campaigns) {
     for (const run of campaign.runs) {
       for (const compound of run.compounds) {
-        if (compound.status !== 'COMPLETED') continue;
+        if (compound.status !== 'COMPLETED' && compound.status !== 'DOWNLOAD_FAILED') continue;
         if (!compound.boltz_job_id || !compound.submitted_at) continue;
 
         const cifPath = path.join(
@@ -244,13 +924,14 @@ export function scanIncompleteDownloads(
           'sample_0_structure.cif',
         );
 
-        if (!fs.existsSync(cifPath)) {
+        if (compound.status === 'DOWNLOAD_FAILED' || !fs.existsSync(cifPath)) {
           incomplete.push({
             compound_id: compound.id,
             boltz_job_id: compound.boltz_job_id,
             campaign_id: campaign.id,
             run_id: run.id,
             submitted_at: compound.submitted_at,
+            run_timeout_secs: run.params.timeout_secs,
           });
         }
       }
@@ -0,0 +1,203 @@
+import pLimit from 'p-limit';
+import { v4 as uuidv4 } from 'uuid';
+import type {
+  Campaign,
+  Compound,
+  CompoundInput,
+  CompoundStatusEvent,
+  JobStatus,
+  Run,
+  RunParams,
+} from '../models/types';
+import { computeInputHash, singleLigand } from '../models/state';
+import type { AppServices } from './index';
+import {
+  buildInferenceInputForMode,
+  buildInferenceOptions,
+  buildPredictionName,
+} from './boltz-client';
+import { humanizeError } from './humanize-error';
+import { persistState, sanitiseFolderName, uniqueFolderName } from './storage';
+
+/**
+ * Create PENDING compounds with folder names unique among existingFolders.
+ * input_hash is computed against the campaign target and run params.
+ * protein_only runs get compounds with empty SMILES and no ligands.
+ */
+export function buildCompounds(
+  inputs: CompoundInput[],
+  existingFolders: string[],
+  campaign: Campaign,
+  params: RunParams,
+): Compound[] {
+  const folders = [...existingFolders];
+  return inputs.map((c) => {
+    const base = sanitiseFolderName(c.name);
+    const folder = uniqueFolderName(base, folders);
+    folders.push(folder);
+    const ligands = params.mode === 'protein_only' ? [] : (c.ligands ?? singleLigand(c.smiles));
+    return {
+      id: uuidv4(),
+      display_name: c.name,
+      folder_name: folder,
+      smiles: ligands[0]?.smiles ?? '',
+      smiles_list: ligands,
+      boltz_job_id: null,
+      status: 'PENDING' as JobStatus,
+      submitted_at: null,
+      completed_at: null,
+      metrics: null,
+      error_message: null,
+      download_error: null,
+      download_attempts: 0,
+      last_download_attempt_at: null,
+      download_checksum: null,
+      cif_checksum: null,
+      input_hash: computeInputHash(
+        campaign.target_type,
+        campaign.target_sequence,
+        ligands,
+        params,
+      ),
+      note: null,
+      archived: false,
+      archived_at: null,
+    };
+  });
+}
+
+// Compounds with a submission in flight, so a resume can't double-submit
+const inFlight = new Set<string>();
+
+/**
+ * Submit compounds to the Boltz API with bounded concurrency.
+ * Each compound moves PENDING → CREATED (or FAILED) and emits a status event.
+ * Queued compounds are left PENDING once the run is paused.
+ * State is persisted once all submissions have settled.
+ */
+export async function submitCompounds(
+  services: AppServices,
+  apiKey: string,
+  campaign: Campaign,
+  run: Run,
+  compounds: Compound[],
+): Promise<void> {
+  const { state, client, eventBus } = services;
+  const limit = pLimit(services.submitConcurrency);
+  const runId = run.id;
+  const campaignId = campaign.id;
+
+  // Shared rate-limit gate: when any submission gets a 429,
+  // pause all queued submissions for the Retry-After duration.
+  const sleep = (ms: number) => new Promise<void>((r) => setTimeout(r, ms));
+  let pauseUntil = 0;
+
+  function triggerPause(delayMs: number): void {
+    pauseUntil = Math.max(pauseUntil, Date.now() + delayMs);
+  }
+
+  async function waitForGate(): Promise<void> {
+    let remaining = pauseUntil - Date.now();
+    while (remaining > 0) {
+      await sleep(remaining);
+      remaining = pauseUntil - Date.now();
+    }
+  }
+
+  const tasks = compounds.map((compound) =>
+    limit(async () => {
+      await waitForGate();
+      if (state.findRun(runId)?.paused) return;
+      if (inFlight.has(compound.id)) return;
+      if (state.findCompound(compound.id)?.status !== 'PENDING') return; // e.g. cancelled while queued
+
+      const inferenceInput = buildInferenceInputForMode(
+        run.params.mode,
+        campaign.target_sequence,
+        compound.smiles_list,
+        campaign.target_type,
+      );
+      const inferenceOptions = buildInferenceOptions(run.params);
+      const now = new Date().toISOString();
+
+      inFlight.add(compound.id);
+      try {
+        const predictionName = buildPredictionName(
+          campaign.display_name,
+          run.display_name,
+          compound.display_name,
+        );
+        const resp = await client.submitPrediction(
+          apiKey,
+          inferenceInput,
+          inferenceOptions,
+          predictionName,
+          { onRateLimited: triggerPause },
+        );
+
+        // Update compound state
+        const liveCompound = state.findCompound(compound.id);
+        if (liveCompound) {
+          liveCompound.boltz_job_id = resp.prediction_id;
+          liveCompound.status = 'CREATED';
+          liveCompound.submitted_at = now;
+        }
+        state.markDirty();
+
+        const evt: CompoundStatusEvent = {
+          compound_id: compound.id,
+          run_id: runId,
+          campaign_id: campaignId,
+          status: 'CREATED',
+          metrics: null,
+          completed_at: null,
+          download_error: liveCompound?.download_error ?? null,
+        };
+        eventBus.emit('compound-status-changed', evt);
+      } catch (e) {
+        const msg = humanizeError(e);
+        console.error(`Failed to submit compound ${compound.id}:`, e);
+
+        const liveCompound = state.findCompound(compound.id);
+        if (liveCompound) {
+          liveCompound.status = 'FAILED';
+          liveCompound.completed_at = now;
+          liveCompound.error_message = msg;
+        }
+        state.markDirty();
+
+        const evt: CompoundStatusEvent = {
+          compound_id: compound.id,
+          run_id: runId,
+          campaign_id: campaignId,
+          status: 'FAILED',
+          metrics: null,
+          completed_at: now,
+          download_error: liveCompound?.download_error ?? null,
+        };
+        eventBus.emit('compound-status-changed', evt);
+      } finally {
+        inFlight.delete(compound.id);
+      }
+    }),
+  );
+
+  await Promise.allSettled(tasks);
+  persistState(state.rootDir, state.data);
+}
+
+/**
+ * Ask the API to stop jobs that were cancelled locally. Runs in the
+ * background; failures are logged and never undo the local cancellation.
+ */
+export function cancelRemotePredictions(services: AppServices, jobIds: string[]): void {
+  const apiKey = services.state.activeApiKey();
+  if (!apiKey || jobIds.length === 0) return;
+
+  const limit = pLimit(services.submitConcurrency);
+  for (const jobId of jobIds) {
+    limit(() => services.client.cancelPrediction(apiKey, jobId)).catch((err) => {
+      console.warn(`Failed to cancel prediction ${jobId} on the API:`, err);
+    });
+  }
+}
@@ -0,0 +1,218 @@
+import fs from 'node:fs';
+import zlib from 'node:zlib';
+import { promisify } from 'node:util';
+
+// Minimal ZIP (PKWARE APPNOTE) reader/writer: deflate or stored entries,
+// no ZIP64, no encryption. Enough for campaign export/import archives.
+
+const deflateRaw = promisify(zlib.deflateRaw);
+
+const LOCAL_HEADER_SIG = 0x04034b50;
+const CENTRAL_HEADER_SIG = 0x02014b50;
+const END_OF_CENTRAL_DIR_SIG = 0x06054b50;
+const LOCAL_HEADER_SIZE = 30;
+const CENTRAL_HEADER_SIZE = 46;
+const END_OF_CENTRAL_DIR_SIZE = 22;
+const MAX_COMMENT_SIZE = 0xffff;
+const ZIP32_LIMIT = 0xffffffff;
+
+const VERSION = 20; // 2.0: deflate
+const FLAG_UTF8 = 0x0800;
+const METHOD_STORED = 0;
+const METHOD_DEFLATE = 8;
+
+interface CentralEntry {
+  name: Buffer;
+  crc: number;
+  compressedSize: number;
+  size: number;
+  offset: number;
+  time: number;
+  date: number;
+}
+
+function dosDateTime(d: Date): { time: number; date: number } {
+  return {
+    time: (d.getHours() << 11) | (d.getMinutes() << 5) | Math.floor(d.getSeconds() / 2),
+    date: ((d.getFullYear() - 1980) << 9) | ((d.getMonth() + 1) << 5) | d.getDate(),
+  };
+}
+
+// ── Writer ───────────────────────────────────────────────────────────
+
+/**
+ * Streams entries to disk one at a time, so only a single file's
+ * contents are held in memory. Call close() to write the central directory.
+ */
+export class ZipWriter {
+  private fd: number;
+  private offset = 0;
+  private entries: CentralEntry[] = [];
+
+  constructor(filePath: string) {
+    this.fd = fs.openSync(filePath, 'w');
+  }
+
+  /** Bytes written so far (the archive size once closed). */
+  get size(): number {
+    return this.offset;
+  }
+
+  async add(name: string, data: Buffer): Promise<void> {
+    const compressed = await deflateRaw(data);
+    const nameBuf = Buffer.from(name, 'utf-8');
+    const { time, date } = dosDateTime(new Date());
+    const crc = zlib.crc32(data);
+
+    if (this.offset + compressed.length > ZIP32_LIMIT || data.length > ZIP32_LIMIT) {
+      throw new Error('Archive exceeds the 4 GB ZIP limit');
+    }
+
+    const header = Buffer.alloc(LOCAL_HEADER_SIZE);
+    header.writeUInt32LE(LOCAL_HEADER_SIG, 0);
+    header.writeUInt16LE(VERSION, 4);
+    header.writeUInt16LE(FLAG_UTF8, 6);
+    header.writeUInt16LE(METHOD_DEFLATE, 8);
+    header.writeUInt16LE(time, 10);
+    header.writeUInt16LE(date, 12);
+    header.writeUInt32LE(crc, 14);
+    header.writeUInt32LE(compressed.length, 18);
+    header.writeUInt32LE(data.length, 22);
+    header.writeUInt16LE(nameBuf.length, 26);
+    header.writeUInt16LE(0, 28); // extra field length
+
+    this.entries.push({
+      name: nameBuf,
+      crc,
+      compressedSize: compressed.length,
+      size: data.length,
+      offset: this.offset,
+      time,
+      date,
+    });
+    this.write(header);
+    this.write(nameBuf);
+    this.write(compressed);
+  }
+
+  close(): void {
+    const cdOffset = this.offset;
+    for (const e of this.entries) {
+      const header = Buffer.alloc(CENTRAL_HEADER_SIZE);
+      header.writeUInt32LE(CENTRAL_HEADER_SIG, 0);
+      header.writeUInt16LE(VERSION, 4); // version made by
+      header.writeUInt16LE(VERSION, 6); // version needed
+      header.writeUInt16LE(FLAG_UTF8, 8);
+      header.writeUInt16LE(METHOD_DEFLATE, 10);
+      header.writeUInt16LE(e.time, 12);
+      header.writeUInt16LE(e.date, 14);
+      header.writeUInt32LE(e.crc, 16);
+      header.writeUInt32LE(e.compressedSize, 20);
+      header.writeUInt32LE(e.size, 24);
+      header.writeUInt16LE(e.name.length, 28);
+      // extra/comment length, disk start, internal/external attrs stay 0
+      header.writeUInt32LE(e.offset, 42);
+      this.write(header);
+      this.write(e.name);
+    }
+
+    const end = Buffer.alloc(END_OF_CENTRAL_DIR_SIZE);
+    end.writeUInt32LE(END_OF_CENTRAL_DIR_SIG, 0);
+    end.writeUInt16LE(this.entries.length, 8);
+    end.writeUInt16LE(this.entries.length, 10);
+    end.writeUInt32LE(this.offset - cdOffset, 12);
+    end.writeUInt32LE(cdOffset, 16);
+    this.write(end);
+
+    fs.closeSync(this.fd);
+  }
+
+  /** Close the file handle without finishing the archive (error path). */
+  abort(): void {
+    fs.closeSync(this.fd);
+  }
+
+  private write(buf: Buffer): void {
+    fs.writeSync(this.fd, buf);
+    this.offset += buf.length;
+  }
+}
+
+// ── Reader ───────────────────────────────────────────────────────────
+
+export interface ZipEntry {
+  name: string;
+  size: number;
+  isDirectory: boolean;
+  /** Decompress and CRC-check the entry. */
+  read(): Buffer;
+}
+
+/**
+ * Parse the central directory of a ZIP archive held in memory.
+ * Throws on a malformed or unsupported archive; entry data is only
+ * decompressed when read() is called.
+ */
+export function readZip(buf: Buffer): ZipEntry[] {
+  const eocd = findEndOfCentralDir(buf);
+  const count = buf.readUInt16LE(eocd + 10);
+  let pos = buf.readUInt32LE(eocd + 16);
+
+  const entries: ZipEntry[] = [];
+  for (let i = 0; i < count; i++) {
+    if (pos + CENTRAL_HEADER_SIZE > buf.length || buf.readUInt32LE(pos) !== CENTRAL_HEADER_SIG) {
+      throw new Error('Corrupt ZIP central directory');
+    }
+    const flags = buf.readUInt16LE(pos + 8);
+    const method = buf.readUInt16LE(pos + 10);
+    const crc = buf.readUInt32LE(pos + 16);
+    const compressedSize = buf.readUInt32LE(pos + 20);
+    const size = buf.readUInt32LE(pos + 24);
+    const nameLen = buf.readUInt16LE(pos + 28);
+    const extraLen = buf.readUInt16LE(pos + 30);
+    const commentLen = buf.readUInt16LE(pos + 32);
+    const localOffset = buf.readUInt32LE(pos + 42);
+    const name = buf.subarray(pos + CENTRAL_HEADER_SIZE, pos + CENTRAL_HEADER_SIZE + nameLen)
+      .toString(flags & FLAG_UTF8 ? 'utf-8' : 'latin1');
+    pos += CENTRAL_HEADER_SIZE + nameLen + extraLen + commentLen;
+
+    if (flags & 0x1) throw new Error(`Encrypted ZIP entries are not supported: ${name}`);
+    if (method !== METHOD_STORED && method !== METHOD_DEFLATE) {
+      throw new Error(`Unsupported ZIP compression method ${method}: ${name}`);
+    }
+    if (buf.readUInt32LE(localOffset) !== LOCAL_HEADER_SIG) {
+      throw new Error(`Corrupt ZIP local header: ${name}`);
+    }
+    const dataStart =
+      localOffset +
+      LOCAL_HEADER_SIZE +
+      buf.readUInt16LE(localOffset + 26) +
+      buf.readUInt16LE(localOffset + 28);
+    if (dataStart + compressedSize > buf.length) {
+      throw new Error(`Truncated ZIP entry: ${name}`);
+    }
+
+    entries.push({
+      name,
+      size,
+      isDirectory: name.endsWith('/'),
+      read() {
+        const raw = buf.subarray(dataStart, dataStart + compressedSize);
+        const data = method === METHOD_DEFLATE ? zlib.inflateRawSync(raw) : Buffer.from(raw);
+        if (zlib.crc32(data) !== crc) {
+          throw new Error(`CRC mismatch in ZIP entry: ${name}`);
+        }
+        return data;
+      },
+    });
+  }
+  return entries;
+}
+
+function findEndOfCentralDir(buf: Buffer): number {
+  const stop = Math.max(0, buf.length - END_OF_CENTRAL_DIR_SIZE - MAX_COMMENT_SIZE);
+  for (let i = buf.length - END_OF_CENTRAL_DIR_SIZE; i >= stop; i--) {
+    if (buf.readUInt32LE(i) === END_OF_CENTRAL_DIR_SIG) return i;
+  }
+  throw new Error('Not a ZIP archive');
+}
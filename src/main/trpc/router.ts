@@ -6,6 +6,8 @@ import { compoundsRouter } from './routers/compounds';
 import { actionsRouter } from './routers/actions';
 import { windowRouter } from './routers/window';
 import { autoUpdateRouter } from './routers/auto-update';
+import { workspaceRouter } from './routers/workspace';
+import { templatesRouter } from './routers/templates';
 
 export const appRouter = router({
   settings: settingsRouter,
@@ -15,6 +17,8 @@ export const appRouter = router({
   actions: actionsRouter,
   window: windowRouter,
   autoUpdate: autoUpdateRouter,
+  workspace: workspaceRouter,
+  templates: templatesRouter,
 });
 
 export type AppRouter = typeof appRouter;
@@ -2,8 +2,29 @@ import { z } from 'zod';
 import { app, dialog, shell } from 'electron';
 import fs from 'node:fs';
 import path from 'node:path';
+import { spawn } from 'node:child_process';
 import { router, publicProcedure } from '../trpc';
-import { resolveCompoundPath } from '../../services/storage';
+import { openPathInFileManager, resolveCompoundPath } from '../../services/storage';
+import { readExternalViewerPath } from '../../services/prefs';
+
+/** Launch a configured viewer detached; rejects if the binary can't start. */
+function launchViewer(viewerPath: string, filePath: string): Promise<void> {
+  const isAppBundle = process.platform === 'darwin' && viewerPath.endsWith('.app');
+  const [command, args] = isAppBundle
+    ? ['open', ['-a', viewerPath, filePath]]
+    : [viewerPath, [filePath]];
+
+  return new Promise((resolve, reject) => {
+    const child = spawn(command, args, { detached: true, stdio: 'ignore' });
+    child.once('spawn', () => {
+      child.unref();
+      resolve();
+    });
+    child.once('error', (err) => {
+      reject(new Error(`Failed to launch viewer ${path.basename(viewerPath)}: ${err.message}`));
+    });
+  });
+}
 
 export const actionsRouter = router({
   openCsvFile: publicProcedure.mutation(async () => {
@@ -23,9 +44,17 @@ export const actionsRouter = router({
 
   openInFinder: publicProcedure
     .input(z.object({ compoundId: z.string().uuid() }))
-    .mutation(({ ctx, input }) => {
+    .mutation(async ({ ctx, input }) => {
+      const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
+      await openPathInFileManager(compoundDir, true);
+    }),
+
+  /** Open the compound's output folder itself rather than revealing it in its parent */
+  openCompoundFolder: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(async ({ ctx, input }) => {
       const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
-      shell.showItemInFolder(compoundDir);
+      await openPathInFileManager(compoundDir, false);
     }),
 
   openExternal: publicProcedure
@@ -35,13 +64,27 @@ export const actionsRouter = router({
         sampleIndex: z.number().int().min(0),
       }),
     )
-    .mutation(({ ctx, input }) => {
+    .mutation(async ({ ctx, input }) => {
       const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
       const cifPath = path.join(
         compoundDir,
         `sample_${input.sampleIndex}_structure.cif`,
       );
-      shell.openPath(cifPath);
+      if (!fs.existsSync(cifPath)) {
+        throw new Error('Structure file not found');
+      }
+
+      const viewerPath = readExternalViewerPath();
+      if (viewerPath) {
+        await launchViewer(viewerPath, cifPath);
+        return;
+      }
+
+      // openPath resolves to an error message, or '' on success
+      const error = await shell.openPath(cifPath);
+      if (error) {
+        throw new Error(`No application available to open CIF files: ${error}`);
+      }
     }),
 
   exportCsv: publicProcedure
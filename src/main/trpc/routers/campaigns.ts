@@ -1,6 +1,8 @@
 import { z } from 'zod';
 import { v4 as uuidv4 } from 'uuid';
+import { observable } from '@trpc/server/observable';
 import { router, publicProcedure } from '../trpc';
+import { compoundInputSchema } from '../schemas';
 import {
   createCampaignFolder,
   sanitiseFolderName,
@@ -9,71 +11,227 @@ import {
   persistState,
 } from '../../services/storage';
 import { trackEvent } from '../../services/telemetry';
+import { exportCampaignZip, importCampaignZip } from '../../services/campaign-archive';
+import { validateTargetSequence } from '../../models/validation';
+import { parseFasta } from '../../models/fasta';
+import { byDisplayOrder, nextDisplayOrder } from '../../models/state';
+import type { AppState } from '../../models/state';
+import type {
+  Campaign,
+  CampaignMergedEvent,
+  CampaignMetricsSummary,
+  ExportProgressEvent,
+  RankedCompound,
+  RunArchivedEvent,
+  RunSummary,
+  SequenceValidationResult,
+} from '../../models/types';
+import { BINDING_HISTOGRAM_BUCKETS, MAX_FASTA_CAMPAIGNS, isTerminal } from '../../models/types';
+import fs from 'node:fs';
 import path from 'node:path';
 
+const campaignInputSchema = z
+  .object({
+    displayName: z.string().min(1),
+    targetSequence: z.string().min(1),
+    targetType: z.enum(['protein', 'dna', 'rna']).default('protein'),
+    description: z.string().nullable().optional(),
+  })
+  .superRefine((val, ctx) => {
+    try {
+      validateTargetSequence(val.targetSequence.trim().toUpperCase(), val.targetType);
+    } catch (e) {
+      ctx.addIssue({
+        code: z.ZodIssueCode.custom,
+        path: ['targetSequence'],
+        message: e instanceof Error ? e.message : String(e),
+      });
+    }
+  });
+
+function createCampaign(state: AppState, input: z.infer<typeof campaignInputSchema>): Campaign {
+  const targetSequence = input.targetSequence.trim().toUpperCase();
+  const baseName = sanitiseFolderName(input.displayName);
+  const existing = state.data.campaigns.map((c) => c.folder_name);
+  const folderName = uniqueFolderName(baseName, existing);
+
+  const campaign: Campaign = {
+    id: uuidv4(),
+    display_name: input.displayName,
+    folder_name: folderName,
+    target_sequence: targetSequence,
+    target_type: input.targetType,
+    description: input.description ?? null,
+    note: null,
+    archived: false,
+    archived_at: null,
+    created_at: new Date().toISOString(),
+    display_order: nextDisplayOrder(state.data.campaigns),
+    runs: [],
+  };
+
+  state.data.campaigns.push(campaign);
+  state.audit('create', 'campaign', campaign.id, campaign.display_name);
+
+  createCampaignFolder(state.rootDir, folderName);
+  persistState(state.rootDir, state.data);
+
+  trackEvent('campaign_created');
+
+  return campaign;
+}
+
+/** Sequences are compared after the same trim/uppercase normalisation as create */
+function findCampaignBySequence(state: AppState, sequence: string): Campaign | undefined {
+  const normalised = sequence.trim().toUpperCase();
+  return state.data.campaigns.find((c) => !c.archived && c.target_sequence === normalised);
+}
+
+/** Reject a reorder list that isn't exactly the existing set of IDs */
+function assertSameIds(ordered: string[], existing: string[], kind: string): void {
+  const expected = new Set(existing);
+  const seen = new Set<string>();
+  for (const id of ordered) {
+    if (!expected.has(id)) throw new Error(`Unknown ${kind} ID: ${id}`);
+    if (seen.has(id)) throw new Error(`Duplicate ${kind} ID: ${id}`);
+    seen.add(id);
+  }
+  if (seen.size !== expected.size) {
+    throw new Error(`Order is missing ${expected.size - seen.size} ${kind}(s)`);
+  }
+}
+
 export const campaignsRouter = router({
   list: publicProcedure.query(({ ctx }) => {
-    return ctx.services.state.data.campaigns;
+    return [...ctx.services.state.data.campaigns].sort(byDisplayOrder);
   }),
 
   create: publicProcedure
+    .input(campaignInputSchema)
+    .mutation(({ ctx, input }) => {
+      return createCampaign(ctx.services.state, input);
+    }),
+
+  /** A non-archived campaign with this exact target sequence, or null */
+  getBySequence: publicProcedure
+    .input(z.object({ sequence: z.string().min(1) }))
+    .query(({ ctx, input }) => {
+      return findCampaignBySequence(ctx.services.state, input.sequence) ?? null;
+    }),
+
+  /**
+   * Idempotent create for scripts and imports: returns the non-archived
+   * campaign with the same target sequence if there is one. displayName
+   * and description only apply when a new campaign is created.
+   */
+  getOrCreate: publicProcedure
+    .input(campaignInputSchema)
+    .mutation(({ ctx, input }) => {
+      return (
+        findCampaignBySequence(ctx.services.state, input.targetSequence) ??
+        createCampaign(ctx.services.state, input)
+      );
+    }),
+
+  /**
+   * Create one campaign per FASTA entry, named after its header. Every
+   * sequence is validated before anything is created, so a bad entry
+   * aborts the whole import.
+   */
+  createFromFasta: publicProcedure
     .input(
       z.object({
-        displayName: z.string().min(1),
-        targetSequence: z.string().min(1),
+        fastaPath: z.string().min(1),
         targetType: z.enum(['protein', 'dna', 'rna']).default('protein'),
         description: z.string().nullable().optional(),
-      }).superRefine((val, ctx) => {
-        const seq = val.targetSequence.trim().toUpperCase();
-        const valid =
-          val.targetType === 'dna'
-            ? /^[ACGT]+$/.test(seq)
-            : val.targetType === 'rna'
-              ? /^[ACGU]+$/.test(seq)
-              : /^[A-Z]+$/.test(seq);
-        if (!valid) {
-          ctx.addIssue({
-            code: z.ZodIssueCode.custom,
-            path: ['targetSequence'],
-            message:
-              val.targetType === 'dna'
-                ? 'DNA sequence must contain only A, C, G, and T.'
-                : val.targetType === 'rna'
-                  ? 'RNA sequence must contain only A, C, G, and U.'
-                  : 'Protein sequence must contain only amino acid letters.',
-          });
-        }
       }),
     )
-    .mutation(({ ctx, input }) => {
+    .mutation(async ({ ctx, input }) => {
       const { state } = ctx.services;
-      const targetSequence = input.targetSequence.trim().toUpperCase();
-      const baseName = sanitiseFolderName(input.displayName);
-      const existing = state.data.campaigns.map((c) => c.folder_name);
-      const folderName = uniqueFolderName(baseName, existing);
-
-      const campaign = {
-        id: uuidv4(),
-        display_name: input.displayName,
-        folder_name: folderName,
-        target_sequence: targetSequence,
-        target_type: input.targetType,
-        description: input.description ?? null,
-        archived: false,
-        archived_at: null,
-        created_at: new Date().toISOString(),
-        runs: [],
-      };
+      if (!path.isAbsolute(input.fastaPath)) throw new Error('FASTA path must be absolute');
 
-      state.data.campaigns.push(campaign);
-      state.markDirty();
+      const entries = parseFasta(await fs.promises.readFile(input.fastaPath, 'utf-8'));
+      if (entries.length === 0) throw new Error('No sequences found in FASTA file');
+      if (entries.length > MAX_FASTA_CAMPAIGNS) {
+        throw new Error(
+          `FASTA file has ${entries.length} sequences (maximum ${MAX_FASTA_CAMPAIGNS} per import)`,
+        );
+      }
+      for (const entry of entries) {
+        try {
+          validateTargetSequence(entry.sequence, input.targetType);
+        } catch (e) {
+          const msg = e instanceof Error ? e.message : String(e);
+          throw new Error(`${entry.header}: ${msg}`);
+        }
+      }
+
+      const folders = state.data.campaigns.map((c) => c.folder_name);
+      const now = new Date().toISOString();
+      const firstOrder = nextDisplayOrder(state.data.campaigns);
+      const campaigns = entries.map((entry, i): Campaign => {
+        const folderName = uniqueFolderName(sanitiseFolderName(entry.header), folders);
+        folders.push(folderName);
+        return {
+          id: uuidv4(),
+          display_name: entry.header,
+          folder_name: folderName,
+          target_sequence: entry.sequence,
+          target_type: input.targetType,
+          description: input.description ?? null,
+          note: null,
+          archived: false,
+          archived_at: null,
+          created_at: now,
+          display_order: firstOrder + i,
+          runs: [],
+        };
+      });
 
-      createCampaignFolder(state.rootDir, folderName);
+      for (const campaign of campaigns) {
+        createCampaignFolder(state.rootDir, campaign.folder_name);
+      }
+      for (const campaign of campaigns) {
+        state.data.campaigns.push(campaign);
+        state.audit('create', 'campaign', campaign.id, `${campaign.display_name} (FASTA)`);
+      }
       persistState(state.rootDir, state.data);
 
       trackEvent('campaign_created');
 
-      return campaign;
+      return campaigns;
+    }),
+
+  validateSequence: publicProcedure
+    .input(
+      z.object({
+        sequence: z.string(),
+        targetType: z.enum(['protein', 'dna', 'rna']).default('protein'),
+      }),
+    )
+    .query(({ input }) => {
+      const seq = input.sequence.trim().toUpperCase();
+      const result: SequenceValidationResult = { valid: true, length: seq.length, error: null };
+      try {
+        validateTargetSequence(seq, input.targetType);
+      } catch (e) {
+        result.valid = false;
+        result.error = e instanceof Error ? e.message : String(e);
+      }
+      return result;
+    }),
+
+  checkDuplicateSmiles: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        compounds: z.array(compoundInputSchema),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (!state.findCampaign(input.campaignId)) throw new Error('Campaign not found');
+      return state.findDuplicateSmilesInCampaign(input.campaignId, input.compounds);
     }),
 
   rename: publicProcedure
@@ -106,7 +264,147 @@ export const campaignsRouter = router({
       // Update state after disk success
       campaign.display_name = input.newName;
       campaign.folder_name = newFolder;
-      state.markDirty();
+      state.audit('rename', 'campaign', campaign.id, input.newName);
+      persistState(state.rootDir, state.data);
+    }),
+
+  /**
+   * Move every run from source into target, then remove the source campaign
+   * and its folder. The target keeps its own metadata. Campaigns with runs
+   * still in progress are refused unless force is set.
+   */
+  merge: publicProcedure
+    .input(
+      z.object({
+        sourceCampaignId: z.string().uuid(),
+        targetCampaignId: z.string().uuid(),
+        force: z.boolean().default(false),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+      if (input.sourceCampaignId === input.targetCampaignId) {
+        throw new Error('Cannot merge a campaign into itself');
+      }
+      const source = state.findCampaign(input.sourceCampaignId);
+      if (!source) throw new Error('Source campaign not found');
+      const target = state.findCampaign(input.targetCampaignId);
+      if (!target) throw new Error('Target campaign not found');
+
+      if (!input.force) {
+        for (const campaign of [source, target]) {
+          const active = campaign.runs.filter((r) => r.compounds.some((c) => !isTerminal(c.status)));
+          if (active.length > 0) {
+            throw new Error(
+              `Campaign "${campaign.display_name}" has ${active.length} run(s) in progress`,
+            );
+          }
+        }
+      }
+
+      // Move one run at a time so state matches disk if a rename fails part-way
+      const movedRunIds: string[] = [];
+      try {
+        for (const run of [...source.runs]) {
+          const newFolder = uniqueFolderName(
+            run.folder_name,
+            target.runs.map((r) => r.folder_name),
+          );
+          const oldPath = path.join(state.rootDir, source.folder_name, run.folder_name);
+          if (fs.existsSync(oldPath)) {
+            renameFolder(oldPath, path.join(state.rootDir, target.folder_name, newFolder));
+          }
+          run.folder_name = newFolder;
+          run.display_order = nextDisplayOrder(target.runs);
+          source.runs.splice(source.runs.indexOf(run), 1);
+          target.runs.push(run);
+          movedRunIds.push(run.id);
+        }
+      } finally {
+        if (movedRunIds.length > 0) {
+          state.audit('merge', 'campaign', target.id, `${movedRunIds.length} runs from "${source.display_name}"`);
+          persistState(state.rootDir, state.data);
+        }
+      }
+
+      state.data.campaigns = state.data.campaigns.filter((c) => c.id !== source.id);
+      state.audit('delete', 'campaign', source.id, `merged into "${target.display_name}"`);
+      persistState(state.rootDir, state.data);
+
+      // The folder should be empty now; leave it if anything unexpected remains
+      const sourceDir = path.join(state.rootDir, source.folder_name);
+      try {
+        fs.rmdirSync(sourceDir);
+      } catch (e) {
+        console.warn(`Could not remove merged campaign folder ${sourceDir}:`, e);
+      }
+
+      const event: CampaignMergedEvent = {
+        source_campaign_id: source.id,
+        target_campaign_id: target.id,
+        run_ids: movedRunIds,
+      };
+      eventBus.emit('campaign-merged', event);
+
+      return target;
+    }),
+
+  /** Set a custom campaign order; orderedIds must list every campaign exactly once */
+  reorder: publicProcedure
+    .input(z.object({ orderedIds: z.array(z.string().uuid()) }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const campaigns = state.data.campaigns;
+      assertSameIds(input.orderedIds, campaigns.map((c) => c.id), 'campaign');
+
+      const order = new Map(input.orderedIds.map((id, i) => [id, i]));
+      for (const campaign of campaigns) {
+        const position = order.get(campaign.id)!;
+        if (campaign.display_order === position) continue;
+        campaign.display_order = position;
+        state.audit('reorder', 'campaign', campaign.id, `position ${position}`);
+      }
+      persistState(state.rootDir, state.data);
+    }),
+
+  /** Set a custom run order within a campaign; orderedRunIds must list every run exactly once */
+  reorderRuns: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        orderedRunIds: z.array(z.string().uuid()),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+      assertSameIds(input.orderedRunIds, campaign.runs.map((r) => r.id), 'run');
+
+      const order = new Map(input.orderedRunIds.map((id, i) => [id, i]));
+      for (const run of campaign.runs) {
+        const position = order.get(run.id)!;
+        if (run.display_order === position) continue;
+        run.display_order = position;
+        state.audit('reorder', 'run', run.id, `position ${position}`);
+      }
+      persistState(state.rootDir, state.data);
+    }),
+
+  setNote: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        note: z.string().nullable(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      campaign.note = input.note;
+      state.audit('set_note', 'campaign', campaign.id);
       persistState(state.rootDir, state.data);
     }),
 
@@ -118,7 +416,7 @@ export const campaignsRouter = router({
 
       campaign.archived = true;
       campaign.archived_at = new Date().toISOString();
-      ctx.services.state.markDirty();
+      ctx.services.state.audit('archive', 'campaign', campaign.id);
     }),
 
   unarchive: publicProcedure
@@ -129,7 +427,32 @@ export const campaignsRouter = router({
 
       campaign.archived = false;
       campaign.archived_at = null;
-      ctx.services.state.markDirty();
+      ctx.services.state.audit('unarchive', 'campaign', campaign.id);
+    }),
+
+  /** Archive every completed, unarchived run in the campaign. Returns how many were archived. */
+  archiveCompletedRuns: publicProcedure
+    .input(z.object({ campaignId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      const now = new Date().toISOString();
+      const archived = campaign.runs.filter((r) => r.completed_at && !r.archived);
+      for (const run of archived) {
+        run.archived = true;
+        run.archived_at = now;
+        state.audit('archive', 'run', run.id, 'bulk');
+      }
+      if (archived.length === 0) return 0;
+
+      persistState(state.rootDir, state.data);
+      for (const run of archived) {
+        const event: RunArchivedEvent = { run_id: run.id, campaign_id: campaign.id, archived_at: now };
+        eventBus.emit('run-archived', event);
+      }
+      return archived.length;
     }),
 
   updateDescription: publicProcedure
@@ -145,7 +468,170 @@ export const campaignsRouter = router({
       if (!campaign) throw new Error('Campaign not found');
 
       campaign.description = input.description;
-      state.markDirty();
+      state.audit('update_description', 'campaign', campaign.id);
+      persistState(state.rootDir, state.data);
+    }),
+
+  updateTargetSequence: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        targetSequence: z.string().min(1),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      const targetSequence = input.targetSequence.trim().toUpperCase();
+      validateTargetSequence(targetSequence, campaign.target_type);
+
+      // In-flight predictions were submitted against the old sequence
+      const active = campaign.runs.some((r) => r.compounds.some((c) => !isTerminal(c.status)));
+      if (active) {
+        throw new Error('Cannot change the target sequence while compounds are still in progress');
+      }
+
+      campaign.target_sequence = targetSequence;
+      state.audit('update_target_sequence', 'campaign', campaign.id);
       persistState(state.rootDir, state.data);
     }),
+
+  getRuns: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        includeArchived: z.boolean().default(false),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const campaign = ctx.services.state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      return campaign.runs
+        .filter((r) => input.includeArchived || !r.archived)
+        .sort(byDisplayOrder)
+        .map(({ compounds, ...run }): RunSummary => ({
+          ...run,
+          compounds: compounds.map((c) => ({
+            id: c.id,
+            display_name: c.display_name,
+            status: c.status,
+            binding_confidence: c.metrics?.affinity?.binding_confidence ?? null,
+          })),
+        }));
+    }),
+
+  getMetricsSummary: publicProcedure
+    .input(z.object({ campaignId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const campaign = ctx.services.state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      const summary: CampaignMetricsSummary = {
+        best_binding: null,
+        mean_binding_confidence: null,
+        stddev_binding_confidence: null,
+        binding_histogram: new Array(BINDING_HISTOGRAM_BUCKETS).fill(0),
+        top_by_optimization: [],
+        completed_count: 0,
+        failed_count: 0,
+        running_count: 0,
+      };
+
+      const confidences: number[] = [];
+      const scored: Omit<RankedCompound, 'rank'>[] = [];
+      for (const run of campaign.runs) {
+        for (const compound of run.compounds) {
+          // Same grouping as the sidebar's run progress summary
+          if (
+            compound.status === 'FAILED' ||
+            compound.status === 'TIMED_OUT' ||
+            compound.status === 'CANCELLED' ||
+            compound.status === 'DOWNLOAD_FAILED'
+          ) {
+            summary.failed_count++;
+          } else if (!isTerminal(compound.status)) {
+            summary.running_count++;
+          }
+          if (compound.status !== 'COMPLETED') continue;
+          summary.completed_count++;
+
+          const affinity = compound.metrics?.affinity;
+          if (!affinity) continue;
+          const bc = affinity.binding_confidence;
+          confidences.push(bc);
+          const bucket = Math.min(
+            Math.max(Math.floor(bc * BINDING_HISTOGRAM_BUCKETS), 0),
+            BINDING_HISTOGRAM_BUCKETS - 1,
+          );
+          summary.binding_histogram[bucket]++;
+          if (!summary.best_binding || bc > summary.best_binding.binding_confidence) {
+            summary.best_binding = {
+              compound_id: compound.id,
+              run_id: run.id,
+              binding_confidence: bc,
+            };
+          }
+          scored.push({ ...compound, campaign_id: campaign.id, run_id: run.id });
+        }
+      }
+
+      if (confidences.length > 0) {
+        const mean = confidences.reduce((a, b) => a + b, 0) / confidences.length;
+        const variance =
+          confidences.reduce((acc, x) => acc + (x - mean) ** 2, 0) / confidences.length;
+        summary.mean_binding_confidence = mean;
+        summary.stddev_binding_confidence = Math.sqrt(variance);
+      }
+
+      scored.sort(
+        (a, b) => b.metrics!.affinity!.optimization_score - a.metrics!.affinity!.optimization_score,
+      );
+      summary.top_by_optimization = scored
+        .slice(0, 5)
+        .map((c, i): RankedCompound => ({ ...c, rank: i + 1 }));
+
+      return summary;
+    }),
+
+  exportZip: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        destPath: z.string().min(1),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      return exportCampaignZip(ctx.services, input.campaignId, input.destPath);
+    }),
+
+  importZip: publicProcedure
+    .input(z.object({ zipPath: z.string().min(1) }))
+    .mutation(({ ctx, input }) => {
+      return importCampaignZip(ctx.services, input.zipPath);
+    }),
+
+  // ── Subscriptions ──────────────────────────────────────────────────
+
+  onMerged: publicProcedure.subscription(({ ctx }) => {
+    return observable<CampaignMergedEvent>((emit) => {
+      const handler = (event: CampaignMergedEvent) => emit.next(event);
+      ctx.services.eventBus.on('campaign-merged', handler);
+      return () => {
+        ctx.services.eventBus.off('campaign-merged', handler);
+      };
+    });
+  }),
+
+  onExportProgress: publicProcedure.subscription(({ ctx }) => {
+    return observable<ExportProgressEvent>((emit) => {
+      const handler = (event: ExportProgressEvent) => emit.next(event);
+      ctx.services.eventBus.on('export-progress', handler);
+      return () => {
+        ctx.services.eventBus.off('export-progress', handler);
+      };
+    });
+  }),
 });
@@ -1,22 +1,110 @@
 import { z } from 'zod';
 import fs from 'node:fs';
 import path from 'node:path';
+import type { EventEmitter } from 'node:events';
 import { observable } from '@trpc/server/observable';
 import { router, publicProcedure } from '../trpc';
-import { resolveCompoundPath, persistState } from '../../services/storage';
 import {
-  buildInferenceInput,
+  resolveCompoundPath,
+  persistState,
+  renameFolder,
+  sanitiseFolderName,
+  uniqueFolderName,
+} from '../../services/storage';
+import {
+  buildInferenceInputForMode,
   buildInferenceOptions,
   buildPredictionName,
 } from '../../services/boltz-client';
 import { humanizeError } from '../../services/humanize-error';
+import {
+  buildCompounds,
+  cancelRemotePredictions,
+  submitCompounds,
+} from '../../services/submitter';
+import {
+  sha256,
+  listCompoundFiles,
+  downloadAndStore,
+  isDownloadActive,
+} from '../../services/file-manager';
+import { validateRunParams } from '../../models/validation';
+import { jobStatusSchema, runParamsSchema } from '../schemas';
+import { computeInputHash, singleLigand } from '../../models/state';
 import type {
+  BestSample,
+  Campaign,
+  ChecksumVerification,
+  Compound,
+  CompoundWithContext,
+  FolderSyncReport,
+  MetricsPercentile,
+  PaginatedCompounds,
+  Run,
+  RankedCompound,
   CompoundStatusEvent,
+  CompoundFilesChangedEvent,
   CompoundFilesReadyEvent,
+  CompoundDownloadProgressEvent,
+  CompoundDownloadStartedEvent,
+  CompoundRenamedEvent,
   RunCompletedEvent,
 } from '../../models/types';
 import { isTerminal } from '../../models/types';
 
+/**
+ * Point folder_name back at the sanitised display name, renaming the folder
+ * on disk first. If the user already renamed it by hand (old folder gone,
+ * new one present), the existing folder is adopted. Returns false when the
+ * names already match; throws when the new name is taken. The caller
+ * persists state.
+ */
+function syncCompoundFolderName(
+  rootDir: string,
+  campaign: Campaign,
+  run: Run,
+  compound: Compound,
+): boolean {
+  const oldFolder = compound.folder_name;
+  const newFolder = sanitiseFolderName(compound.display_name);
+  if (newFolder === oldFolder) return false;
+
+  if (isDownloadActive(compound.id)) {
+    throw new Error(`Cannot rename "${oldFolder}" while its outputs are downloading`);
+  }
+  if (run.compounds.some((c) => c.id !== compound.id && c.folder_name === newFolder)) {
+    throw new Error(`Folder "${newFolder}" is already used by another compound in this run`);
+  }
+
+  const runDir = path.join(rootDir, campaign.folder_name, run.folder_name);
+  const oldPath = path.join(runDir, oldFolder);
+  const newPath = path.join(runDir, newFolder);
+  if (fs.existsSync(oldPath)) {
+    if (fs.existsSync(newPath)) {
+      throw new Error(`Cannot rename "${oldFolder}": "${newFolder}" already exists on disk`);
+    }
+    renameFolder(oldPath, newPath);
+  }
+
+  compound.folder_name = newFolder;
+  return true;
+}
+
+function emitCompoundRenamed(
+  eventBus: EventEmitter,
+  campaign: Campaign,
+  run: Run,
+  compound: Compound,
+): void {
+  eventBus.emit('compound-renamed', {
+    compound_id: compound.id,
+    run_id: run.id,
+    campaign_id: campaign.id,
+    display_name: compound.display_name,
+    folder_name: compound.folder_name,
+  } satisfies CompoundRenamedEvent);
+}
+
 export const compoundsRouter = router({
   get: publicProcedure
     .input(z.object({ compoundId: z.string().uuid() }))
@@ -26,6 +114,270 @@ export const compoundsRouter = router({
       return compound;
     }),
 
+  search: publicProcedure
+    .input(
+      z.object({
+        query: z.string(),
+        scope: z
+          .object({
+            campaign_id: z.string().uuid().optional(),
+            run_id: z.string().uuid().optional(),
+          })
+          .default({}),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      return ctx.services.state.searchCompounds(input.query, input.scope);
+    }),
+
+  listAll: publicProcedure
+    .input(
+      z.object({
+        page: z.number().int().min(0),
+        pageSize: z.number().int().min(1).max(500),
+        filter: z
+          .object({
+            status: jobStatusSchema.optional(),
+            campaign_id: z.string().uuid().optional(),
+            run_id: z.string().uuid().optional(),
+            min_binding_confidence: z.number().min(0).max(1).optional(),
+          })
+          .default({}),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const { status, campaign_id, run_id, min_binding_confidence } = input.filter;
+
+      const matches: CompoundWithContext[] = [];
+      for (const [campaign, run, compound] of ctx.services.state.allCompoundContexts()) {
+        if (status && compound.status !== status) continue;
+        if (campaign_id && campaign.id !== campaign_id) continue;
+        if (run_id && run.id !== run_id) continue;
+        if (min_binding_confidence !== undefined) {
+          const confidence = compound.metrics?.affinity?.binding_confidence;
+          if (confidence == null || confidence < min_binding_confidence) continue;
+        }
+        matches.push({
+          ...compound,
+          campaign_id: campaign.id,
+          campaign_name: campaign.display_name,
+          run_id: run.id,
+          run_name: run.display_name,
+        });
+      }
+
+      const start = input.page * input.pageSize;
+      const result: PaginatedCompounds = {
+        items: matches.slice(start, start + input.pageSize),
+        total: matches.length,
+        page: input.page,
+        page_size: input.pageSize,
+      };
+      return result;
+    }),
+
+  getTop: publicProcedure
+    .input(
+      z.object({
+        scope: z.discriminatedUnion('type', [
+          z.object({ type: z.literal('run'), run_id: z.string().uuid() }),
+          z.object({ type: z.literal('campaign'), campaign_id: z.string().uuid() }),
+        ]),
+        limit: z.number().int().min(1),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const { scope } = input;
+
+      let pairs: [Campaign, Run][];
+      if (scope.type === 'run') {
+        const context = state.findRunContext(scope.run_id);
+        if (!context) throw new Error('Run not found');
+        pairs = [context];
+      } else {
+        const campaign = state.findCampaign(scope.campaign_id);
+        if (!campaign) throw new Error('Campaign not found');
+        pairs = campaign.runs.map((r): [Campaign, Run] => [campaign, r]);
+      }
+
+      const candidates: Omit<RankedCompound, 'rank'>[] = [];
+      for (const [campaign, run] of pairs) {
+        for (const compound of run.compounds) {
+          if (compound.status !== 'COMPLETED' || !compound.metrics?.affinity) continue;
+          candidates.push({ ...compound, campaign_id: campaign.id, run_id: run.id });
+        }
+      }
+
+      // Binding confidence desc, ties broken by optimization score desc
+      candidates.sort((a, b) => {
+        const aa = a.metrics!.affinity!;
+        const bb = b.metrics!.affinity!;
+        return (
+          bb.binding_confidence - aa.binding_confidence ||
+          bb.optimization_score - aa.optimization_score
+        );
+      });
+
+      return candidates
+        .slice(0, input.limit)
+        .map((c, i): RankedCompound => ({ ...c, rank: i + 1 }));
+    }),
+
+  getNote: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const compound = ctx.services.state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+      return compound.note;
+    }),
+
+  rename: publicProcedure
+    .input(
+      z.object({
+        compoundId: z.string().uuid(),
+        newName: z.string().min(1),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+      const context = state.findCompoundContext(input.compoundId);
+      if (!context) throw new Error('Compound not found');
+      const [campaign, run, compound] = context;
+
+      // The download writes into the current folder; moving it mid-transfer loses files
+      if (isDownloadActive(compound.id)) {
+        throw new Error('Cannot rename a compound while its outputs are downloading');
+      }
+
+      const oldFolder = compound.folder_name;
+      const siblings = run.compounds
+        .filter((c) => c.id !== input.compoundId)
+        .map((c) => c.folder_name);
+      const newFolder = uniqueFolderName(sanitiseFolderName(input.newName), siblings);
+
+      // Rename on disk first (the folder only exists once outputs were written)
+      const runDir = path.join(state.rootDir, campaign.folder_name, run.folder_name);
+      if (oldFolder !== newFolder && fs.existsSync(path.join(runDir, oldFolder))) {
+        renameFolder(path.join(runDir, oldFolder), path.join(runDir, newFolder));
+      }
+      compound.folder_name = newFolder;
+
+      compound.display_name = input.newName;
+      state.audit('rename', 'compound', compound.id, input.newName);
+      persistState(state.rootDir, state.data);
+
+      emitCompoundRenamed(eventBus, campaign, run, compound);
+    }),
+
+  /**
+   * Rename the compound's folder to match its display name. Returns whether
+   * anything changed.
+   */
+  syncFolderName: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+      const context = state.findCompoundContext(input.compoundId);
+      if (!context) throw new Error('Compound not found');
+      const [campaign, run, compound] = context;
+
+      if (!syncCompoundFolderName(state.rootDir, campaign, run, compound)) return false;
+      state.audit('sync_folder_name', 'compound', compound.id, compound.folder_name);
+      persistState(state.rootDir, state.data);
+
+      emitCompoundRenamed(eventBus, campaign, run, compound);
+      return true;
+    }),
+
+  /** syncFolderName for every compound; one failure doesn't stop the rest */
+  syncAllFolderNames: publicProcedure.mutation(({ ctx }) => {
+    const { state, eventBus } = ctx.services;
+    const report: FolderSyncReport = { folders_synced: 0, errors: [] };
+
+    for (const [campaign, run, compound] of state.allCompoundContexts()) {
+      try {
+        if (!syncCompoundFolderName(state.rootDir, campaign, run, compound)) continue;
+      } catch (e) {
+        const msg = e instanceof Error ? e.message : String(e);
+        report.errors.push(`${compound.display_name}: ${msg}`);
+        continue;
+      }
+      report.folders_synced++;
+      state.audit('sync_folder_name', 'compound', compound.id, compound.folder_name);
+      emitCompoundRenamed(eventBus, campaign, run, compound);
+    }
+
+    if (report.folders_synced > 0) persistState(state.rootDir, state.data);
+    return report;
+  }),
+
+  setNote: publicProcedure
+    .input(
+      z.object({
+        compoundId: z.string().uuid(),
+        note: z.string().nullable(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const compound = state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+
+      compound.note = input.note;
+      state.audit('set_note', 'compound', compound.id);
+      persistState(state.rootDir, state.data);
+    }),
+
+  getFiles: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
+      if (!fs.existsSync(compoundDir)) {
+        throw new Error('Compound output folder not found');
+      }
+      return listCompoundFiles(compoundDir);
+    }),
+
+  archive: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const compound = ctx.services.state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+      if (!isTerminal(compound.status)) {
+        throw new Error(`Cannot archive a compound that is still in progress (status: ${compound.status})`);
+      }
+
+      compound.archived = true;
+      compound.archived_at = new Date().toISOString();
+      ctx.services.state.audit('archive', 'compound', compound.id);
+    }),
+
+  unarchive: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const compound = ctx.services.state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+
+      compound.archived = false;
+      compound.archived_at = null;
+      ctx.services.state.audit('unarchive', 'compound', compound.id);
+    }),
+
+  /** Emit onFilesChanged when the compound's output files are edited externally */
+  watchFolder: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
+      ctx.services.folderWatcher.watch(input.compoundId, compoundDir);
+    }),
+
+  unwatchFolder: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      ctx.services.folderWatcher.unwatch(input.compoundId);
+    }),
+
   getPoseCif: publicProcedure
     .input(
       z.object({
@@ -39,6 +391,74 @@ export const compoundsRouter = router({
       return fs.readFileSync(cifPath, 'utf-8');
     }),
 
+  /**
+   * The sample with the highest structure_confidence (ties and all-null
+   * metrics fall back to the lowest index) — the default pose to show.
+   */
+  getBestSample: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const compound = ctx.services.state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+      const samples = compound.metrics?.samples ?? [];
+      if (samples.length === 0) throw new Error('Compound has no metrics');
+
+      let best = 0;
+      samples.forEach((s, i) => {
+        const top = samples[best].structure_confidence;
+        if (s.structure_confidence !== null && (top === null || s.structure_confidence > top)) {
+          best = i;
+        }
+      });
+
+      const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
+      const cifPath = path.join(compoundDir, `sample_${best}_structure.cif`);
+      if (!fs.existsSync(cifPath)) throw new Error('Compound has no output files');
+      const paePath = path.join(compoundDir, `sample_${best}_pae.png`);
+
+      const result: BestSample = {
+        sample_index: best,
+        structure_confidence: samples[best].structure_confidence,
+        cif_path: cifPath,
+        pae_path: fs.existsSync(paePath) ? paePath : null,
+      };
+      return result;
+    }),
+
+  /** Rank a compound's affinity against every completed compound in its campaign */
+  getMetricsPercentile: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const found = ctx.services.state.findCompoundContext(input.compoundId);
+      if (!found) throw new Error('Compound not found');
+      const [campaign, , compound] = found;
+      const affinity = compound.metrics?.affinity;
+      if (!affinity) throw new Error('Compound has no affinity metrics');
+
+      const binding: number[] = [];
+      const optimization: number[] = [];
+      for (const run of campaign.runs) {
+        for (const c of run.compounds) {
+          if (c.status !== 'COMPLETED' || !c.metrics?.affinity) continue;
+          binding.push(c.metrics.affinity.binding_confidence);
+          optimization.push(c.metrics.affinity.optimization_score);
+        }
+      }
+      if (binding.length < 2) {
+        throw new Error('Need at least 2 compounds with metrics in the campaign to rank');
+      }
+
+      const percentile = (values: number[], score: number) =>
+        (values.filter((v) => v < score).length / values.length) * 100;
+
+      const result: MetricsPercentile = {
+        binding_confidence_percentile: percentile(binding, affinity.binding_confidence),
+        optimization_score_percentile: percentile(optimization, affinity.optimization_score),
+        sample_count_in_campaign: binding.length,
+      };
+      return result;
+    }),
+
   getPaeImageData: publicProcedure
     .input(
       z.object({
@@ -53,6 +473,26 @@ export const compoundsRouter = router({
       return `data:image/png;base64,${data.toString('base64')}`;
     }),
 
+  verifyChecksum: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const compound = ctx.services.state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+
+      const compoundDir = resolveCompoundPath(ctx.services.state, input.compoundId);
+      const cifPath = path.join(compoundDir, 'sample_0_structure.cif');
+      if (!fs.existsSync(cifPath)) throw new Error('Compound has no output files');
+      const actual = sha256(fs.readFileSync(cifPath));
+
+      const result: ChecksumVerification = {
+        // Downloads from before checksums were recorded have nothing to compare against
+        matches: compound.cif_checksum === null ? null : compound.cif_checksum === actual,
+        expected: compound.cif_checksum,
+        actual,
+      };
+      return result;
+    }),
+
   retry: publicProcedure
     .input(z.object({ compoundId: z.string().uuid() }))
     .mutation(async ({ ctx, input }) => {
@@ -65,8 +505,9 @@ export const compoundsRouter = router({
       if (!isTerminal(compound.status)) {
         throw new Error('Compound is not in a terminal state');
       }
+      validateRunParams(run.params);
 
-      const apiKey = state.data.api_key;
+      const apiKey = state.activeApiKey();
       if (!apiKey) throw new Error('No API key configured');
 
       // Reset compound state
@@ -77,10 +518,28 @@ export const compoundsRouter = router({
       compound.metrics = null;
       compound.error_message = null;
       compound.download_error = null;
-      state.markDirty();
+      compound.download_attempts = 0;
+      compound.last_download_attempt_at = null;
+      compound.download_checksum = null;
+      compound.cif_checksum = null;
+      compound.archived = false; // archived compounds must stay terminal
+      compound.archived_at = null;
+      // The target sequence may have been edited since the first submission
+      compound.input_hash = computeInputHash(
+        campaign.target_type,
+        campaign.target_sequence,
+        compound.smiles_list,
+        run.params,
+      );
+      state.audit('retry', 'compound', compound.id);
 
       // Submit
-      const inferenceInput = buildInferenceInput(campaign.target_sequence, compound.smiles, campaign.target_type);
+      const inferenceInput = buildInferenceInputForMode(
+        run.params.mode,
+        campaign.target_sequence,
+        compound.smiles_list,
+        campaign.target_type,
+      );
       const inferenceOptions = buildInferenceOptions(run.params);
       const now = new Date().toISOString();
 
@@ -104,6 +563,7 @@ export const compoundsRouter = router({
           status: 'CREATED',
           metrics: null,
           completed_at: null,
+          download_error: compound.download_error,
         } satisfies CompoundStatusEvent);
       } catch (e) {
         const msg = humanizeError(e);
@@ -119,12 +579,186 @@ export const compoundsRouter = router({
           status: 'FAILED',
           metrics: null,
           completed_at: now,
+          download_error: compound.download_error,
         } satisfies CompoundStatusEvent);
       }
 
       persistState(state.rootDir, state.data);
     }),
 
+  duplicate: publicProcedure
+    .input(
+      z.object({
+        sourceCompoundId: z.string().uuid(),
+        targetRunId: z.string().uuid(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+
+      const source = state.findCompound(input.sourceCompoundId);
+      if (!source) throw new Error('Compound not found');
+
+      const context = state.findRunContext(input.targetRunId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
+      if (run.archived) throw new Error('Cannot add compounds to an archived run');
+      if (run.completed_at) throw new Error('Cannot add compounds to a completed run');
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      const [compound] = buildCompounds(
+        [{ name: source.display_name, smiles: source.smiles, ligands: source.smiles_list }],
+        run.compounds.map((c) => c.folder_name),
+        campaign,
+        run.params,
+      );
+      run.compounds.push(compound);
+      state.audit('duplicate', 'compound', compound.id, `from ${source.id}`);
+      persistState(state.rootDir, state.data);
+
+      const snapshot = structuredClone(compound);
+
+      // Fire and forget — status events are emitted as submission proceeds
+      submitCompounds(ctx.services, apiKey, campaign, run, [compound]).catch((err) => {
+        console.error(`Submission failed for compound ${compound.id}:`, err);
+      });
+
+      return snapshot;
+    }),
+
+  findMatchingPrediction: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        smiles: z.string().min(1),
+        params: runParamsSchema,
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+
+      const hash = computeInputHash(
+        campaign.target_type,
+        campaign.target_sequence,
+        singleLigand(input.smiles),
+        input.params,
+      );
+      return state.findMatchingPrediction(hash)?.[2] ?? null;
+    }),
+
+  cancel: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+
+      const context = state.findCompoundContext(input.compoundId);
+      if (!context) throw new Error('Compound not found');
+      const [campaign, run, compound] = context;
+
+      if (isTerminal(compound.status)) {
+        throw new Error(`Compound is already finished (status: ${compound.status})`);
+      }
+
+      const now = new Date().toISOString();
+      compound.status = 'CANCELLED';
+      compound.completed_at = now;
+      state.audit('cancel', 'compound', compound.id);
+
+      const runEvent = state.checkRunCompletion(run.id);
+      if (compound.boltz_job_id) cancelRemotePredictions(ctx.services, [compound.boltz_job_id]);
+
+      eventBus.emit('compound-status-changed', {
+        compound_id: compound.id,
+        run_id: run.id,
+        campaign_id: campaign.id,
+        status: 'CANCELLED',
+        metrics: null,
+        completed_at: now,
+        download_error: compound.download_error,
+      } satisfies CompoundStatusEvent);
+      if (runEvent) {
+        eventBus.emit('run-completed', runEvent);
+      }
+
+      persistState(state.rootDir, state.data);
+    }),
+
+  retryDownload: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .mutation(async ({ ctx, input }) => {
+      const { state, client, eventBus } = ctx.services;
+
+      const context = state.findCompoundContext(input.compoundId);
+      if (!context) throw new Error('Compound not found');
+      const [campaign, run, compound] = context;
+
+      if (compound.status !== 'DOWNLOAD_FAILED') {
+        throw new Error(`Compound has no failed download to retry (status: ${compound.status})`);
+      }
+      if (!compound.boltz_job_id || !compound.submitted_at) {
+        throw new Error('Compound has no prediction to download');
+      }
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      // Download URLs expire, so fetch a fresh one rather than resubmitting
+      const prediction = await client.getPredictionStatus(apiKey, compound.boltz_job_id);
+      const output = prediction.prediction_results?.output;
+      if (!output?.download_url) {
+        throw new Error('Prediction has no download URL');
+      }
+
+      compound.status = 'COMPLETED';
+      compound.download_error = null;
+      compound.download_attempts = 0; // a manual retry restarts the automatic backoff
+      state.audit('retry_download', 'compound', compound.id);
+
+      const ref = {
+        compound_id: compound.id,
+        boltz_job_id: compound.boltz_job_id,
+        campaign_id: campaign.id,
+        run_id: run.id,
+        submitted_at: compound.submitted_at,
+        run_timeout_secs: run.params.timeout_secs,
+      };
+      eventBus.emit('compound-download-started', {
+        compound_id: compound.id,
+        run_id: run.id,
+        campaign_id: campaign.id,
+      } satisfies CompoundDownloadStartedEvent);
+
+      // Runs in the background; failures move it back to DOWNLOAD_FAILED
+      downloadAndStore(
+        ctx.services,
+        client,
+        output.download_url,
+        ref,
+        output.checksum ?? null,
+        run.params.diffusion_samples,
+      ).catch((err) => {
+        console.error(`Download retry failed for ${compound.id}:`, err);
+      });
+    }),
+
+  getPredictionRaw: publicProcedure
+    .input(z.object({ compoundId: z.string().uuid() }))
+    .query(async ({ ctx, input }) => {
+      const { state, client } = ctx.services;
+      const compound = state.findCompound(input.compoundId);
+      if (!compound) throw new Error('Compound not found');
+      if (!compound.boltz_job_id) throw new Error('Compound has not been submitted');
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      return client.getPredictionStatusRaw(apiKey, compound.boltz_job_id);
+    }),
+
   // ── Subscriptions ──────────────────────────────────────────────────
 
   onStatusChanged: publicProcedure.subscription(({ ctx }) => {
@@ -147,6 +781,46 @@ export const compoundsRouter = router({
     });
   }),
 
+  onFilesChanged: publicProcedure.subscription(({ ctx }) => {
+    return observable<CompoundFilesChangedEvent>((emit) => {
+      const handler = (event: CompoundFilesChangedEvent) => emit.next(event);
+      ctx.services.eventBus.on('compound-files-changed', handler);
+      return () => {
+        ctx.services.eventBus.off('compound-files-changed', handler);
+      };
+    });
+  }),
+
+  onRenamed: publicProcedure.subscription(({ ctx }) => {
+    return observable<CompoundRenamedEvent>((emit) => {
+      const handler = (event: CompoundRenamedEvent) => emit.next(event);
+      ctx.services.eventBus.on('compound-renamed', handler);
+      return () => {
+        ctx.services.eventBus.off('compound-renamed', handler);
+      };
+    });
+  }),
+
+  onDownloadStarted: publicProcedure.subscription(({ ctx }) => {
+    return observable<CompoundDownloadStartedEvent>((emit) => {
+      const handler = (event: CompoundDownloadStartedEvent) => emit.next(event);
+      ctx.services.eventBus.on('compound-download-started', handler);
+      return () => {
+        ctx.services.eventBus.off('compound-download-started', handler);
+      };
+    });
+  }),
+
+  onDownloadProgress: publicProcedure.subscription(({ ctx }) => {
+    return observable<CompoundDownloadProgressEvent>((emit) => {
+      const handler = (event: CompoundDownloadProgressEvent) => emit.next(event);
+      ctx.services.eventBus.on('compound-download-progress', handler);
+      return () => {
+        ctx.services.eventBus.off('compound-download-progress', handler);
+      };
+    });
+  }),
+
   onRunCompleted: publicProcedure.subscription(({ ctx }) => {
     return observable<RunCompletedEvent>((emit) => {
       const handler = (event: RunCompletedEvent) => emit.next(event);
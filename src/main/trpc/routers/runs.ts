@@ -1,7 +1,15 @@
 import { z } from 'zod';
 import { v4 as uuidv4 } from 'uuid';
-import pLimit from 'p-limit';
+import { observable } from '@trpc/server/observable';
+import Papa from 'papaparse';
 import { router, publicProcedure } from '../trpc';
+import {
+  runParamsSchema,
+  compoundInputSchema,
+  jobStatusSchema,
+  multiLigandCompoundSchema,
+  metricFieldSchema,
+} from '../schemas';
 import {
   createRunFolder,
   createCompoundFolder,
@@ -10,34 +18,156 @@ import {
   renameFolder,
   persistState,
 } from '../../services/storage';
+import { BoltzApiError, estimateGpuSeconds } from '../../services/boltz-client';
+import type { BoltzClient } from '../../services/boltz-client';
 import {
-  BoltzApiError,
-  buildInferenceInput,
-  buildInferenceOptions,
-  buildPredictionName,
-} from '../../services/boltz-client';
+  buildCompounds,
+  cancelRemotePredictions,
+  submitCompounds,
+} from '../../services/submitter';
 import { trackEvent } from '../../services/telemetry';
-import { humanizeError } from '../../services/humanize-error';
+import type { AppServices } from '../../services';
 import type {
+  Campaign,
   Compound,
+  CompoundInput,
+  CorrelationResult,
   Run,
   CompoundStatusEvent,
-  JobStatus,
+  ImportCompoundsResult,
+  PaginatedRunCompounds,
+  ParamsValidationResult,
+  RunMode,
+  RunPreview,
+  MetricStatistics,
+  RunArchivedEvent,
+  RunProgress,
+  RunStatistics,
+  RunTimeline,
+  SubmissionEstimate,
+  SuggestionHint,
+  RunWithCampaign,
+} from '../../models/types';
+import {
+  DEFAULT_LIGAND_CHAIN,
+  MIN_CORRELATION_POINTS,
+  NUMERIC_SAMPLE_METRICS,
+  isTerminal,
 } from '../../models/types';
-import { isTerminal, SUBMIT_CONCURRENCY } from '../../models/types';
+import { validateSmiles, validateRunParams } from '../../models/validation';
+import { findDuplicateSmiles, nextDisplayOrder } from '../../models/state';
+import { parseSdf, writeSdf } from '../../models/sdf';
+import type { SdfAtom } from '../../models/sdf';
+import { parseCifAtoms } from '../../models/cif';
+import { correlationPValue, metricValue, pearson } from '../../models/stats';
+import { findRingSystems } from '../../models/cheminfo';
+import fs from 'node:fs';
 import path from 'node:path';
 
-const runParamsSchema = z.object({
-  recycling_steps: z.number().int().min(1),
-  diffusion_samples: z.number().int().min(1),
-  sampling_steps: z.number().int().min(1),
-  step_scale: z.number().positive(),
-});
+/** Why a compound's SMILES would be rejected, or null if they're all valid */
+function compoundInputError(c: CompoundInput, mode: RunMode): string | null {
+  if (mode === 'protein_only') return null;
+  for (const smiles of c.ligands?.map((l) => l.smiles) ?? [c.smiles]) {
+    try {
+      validateSmiles(smiles);
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      return `Invalid SMILES for "${c.name}": ${msg}`;
+    }
+  }
+  return null;
+}
 
-const compoundInputSchema = z.object({
-  name: z.string().min(1),
-  smiles: z.string().min(1),
-});
+/**
+ * Validate every SMILES up front so nothing is submitted on bad input.
+ * protein_only runs ignore SMILES, so there is nothing to check.
+ */
+function validateCompoundInputs(inputs: CompoundInput[], mode: RunMode): void {
+  for (const c of inputs) {
+    const error = compoundInputError(c, mode);
+    if (error) throw new Error(error);
+  }
+}
+
+/**
+ * Add imported compounds to an existing run and submit them in the
+ * background. Returns immediately with snapshots of the new compounds.
+ */
+function appendCompounds(
+  services: AppServices,
+  apiKey: string,
+  campaign: Campaign,
+  run: Run,
+  inputs: CompoundInput[],
+  warnings: string[],
+  operation: string,
+): ImportCompoundsResult {
+  const { state } = services;
+  if (run.params.mode === 'protein_only') {
+    throw new Error('Cannot import ligands into a protein-only run');
+  }
+  const compounds = buildCompounds(
+    inputs,
+    run.compounds.map((c) => c.folder_name),
+    campaign,
+    run.params,
+  );
+  run.compounds.push(...compounds);
+  state.audit(operation, 'run', run.id, `${compounds.length} compounds`);
+  persistState(state.rootDir, state.data);
+
+  const result: ImportCompoundsResult = {
+    compounds: structuredClone(compounds),
+    warnings,
+  };
+
+  // Fire and forget — persists after all submissions complete
+  submitCompounds(services, apiKey, campaign, run, compounds).catch((err) => {
+    console.error(`Submission failed for run ${run.id}:`, err);
+  });
+
+  return result;
+}
+
+function computeMetricStatistics(values: number[], bucketCount: number): MetricStatistics {
+  const histogram = new Array<number>(bucketCount).fill(0);
+  if (values.length === 0) {
+    return { count: 0, min: null, max: null, mean: null, median: null, stddev: null, histogram };
+  }
+
+  const sorted = [...values].sort((a, b) => a - b);
+  const n = sorted.length;
+  const min = sorted[0];
+  const max = sorted[n - 1];
+  const mean = sorted.reduce((a, b) => a + b, 0) / n;
+  const median = n % 2 === 1 ? sorted[(n - 1) / 2] : (sorted[n / 2 - 1] + sorted[n / 2]) / 2;
+  const variance = sorted.reduce((acc, x) => acc + (x - mean) ** 2, 0) / n;
+
+  const width = (max - min) / bucketCount;
+  for (const v of sorted) {
+    const bucket = width > 0 ? Math.min(Math.floor((v - min) / width), bucketCount - 1) : 0;
+    histogram[bucket]++;
+  }
+
+  return { count: n, min, max, mean, median, stddev: Math.sqrt(variance), histogram };
+}
+
+/** Pre-flight check before creating a run, with a user-facing error on failure. */
+async function verifyApiKey(client: BoltzClient, apiKey: string): Promise<void> {
+  try {
+    await client.testConnection(apiKey);
+  } catch (e) {
+    if (e instanceof BoltzApiError) {
+      if (e.statusCode === 401 || e.statusCode === 403) {
+        throw new Error('API key is invalid or expired. Check Settings.', { cause: e });
+      }
+      if (e.statusCode === 429) {
+        throw new Error('Boltz API rate limit reached. Try again in a few minutes.', { cause: e });
+      }
+    }
+    throw new Error('Cannot reach the Boltz API. Try again in a few minutes.', { cause: e });
+  }
+}
 
 export const runsRouter = router({
   get: publicProcedure
@@ -48,39 +178,467 @@ export const runsRouter = router({
       return run;
     }),
 
-  create: publicProcedure
+  /**
+   * Runs from every campaign, newest first. "active" means some compound
+   * is still in progress; "completed" means the run has completed_at set.
+   */
+  listAll: publicProcedure
+    .input(
+      z.object({
+        includeArchived: z.boolean().default(false),
+        statusFilter: z.enum(['active', 'completed', 'any']).default('any'),
+        createdAfter: z.string().datetime().optional(),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const createdAfter = input.createdAfter ? Date.parse(input.createdAfter) : null;
+
+      const runs: RunWithCampaign[] = [];
+      for (const campaign of ctx.services.state.data.campaigns) {
+        for (const run of campaign.runs) {
+          if (!input.includeArchived && run.archived) continue;
+          if (createdAfter !== null && Date.parse(run.created_at) <= createdAfter) continue;
+          if (
+            input.statusFilter === 'active' &&
+            !run.compounds.some((c) => !isTerminal(c.status))
+          ) {
+            continue;
+          }
+          if (input.statusFilter === 'completed' && !run.completed_at) continue;
+
+          runs.push({ ...run, campaign_id: campaign.id, campaign_name: campaign.display_name });
+        }
+      }
+
+      runs.sort((a, b) => b.created_at.localeCompare(a.created_at));
+      return runs;
+    }),
+
+  getCompounds: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        page: z.number().int().min(0),
+        pageSize: z.number().int().min(1).max(500),
+        statusFilter: jobStatusSchema.optional(),
+        includeArchived: z.boolean().default(true),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const filtered = run.compounds.filter(
+        (c) =>
+          (!input.statusFilter || c.status === input.statusFilter) &&
+          (input.includeArchived || !c.archived),
+      );
+      const start = input.page * input.pageSize;
+
+      const result: PaginatedRunCompounds = {
+        run_id: run.id,
+        display_name: run.display_name,
+        params: run.params,
+        created_at: run.created_at,
+        completed_at: run.completed_at,
+        compounds: filtered.slice(start, start + input.pageSize),
+        total_compounds: run.compounds.length,
+        filtered_total: filtered.length,
+      };
+      return result;
+    }),
+
+  getProgress: publicProcedure
+    .input(z.object({ runId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const total = run.compounds.length;
+      let terminal = 0;
+      let elapsedSumMs = 0;
+      let elapsedCount = 0;
+
+      for (const c of run.compounds) {
+        if (isTerminal(c.status)) terminal++;
+        if (c.status === 'COMPLETED' && c.submitted_at && c.completed_at) {
+          elapsedSumMs += Date.parse(c.completed_at) - Date.parse(c.submitted_at);
+          elapsedCount++;
+        }
+      }
+
+      // ETA = average completed duration × compounds still outstanding
+      const remaining = total - terminal;
+      const estimatedRemainingSecs =
+        elapsedCount > 0
+          ? Math.round((elapsedSumMs / elapsedCount / 1000) * remaining)
+          : null;
+
+      const progress: RunProgress = {
+        total,
+        terminal,
+        percent: total > 0 ? (terminal / total) * 100 : 100,
+        estimated_remaining_secs: estimatedRemainingSecs,
+      };
+      return progress;
+    }),
+
+  /** Per-compound submit/complete times for a Gantt-style chart */
+  getTimeline: publicProcedure
+    .input(z.object({ runId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const compounds = run.compounds.map((c) => {
+        // Compounds cancelled before submission have no meaningful end time
+        const completedAt = c.submitted_at ? c.completed_at : null;
+        return {
+          compound_id: c.id,
+          display_name: c.display_name,
+          submitted_at: c.submitted_at,
+          completed_at: completedAt,
+          duration_secs:
+            c.submitted_at && completedAt
+              ? Math.max(0, Math.round((Date.parse(completedAt) - Date.parse(c.submitted_at)) / 1000))
+              : null,
+          final_status: c.status,
+        };
+      });
+      compounds.sort((a, b) => {
+        if (a.submitted_at === b.submitted_at) return 0;
+        if (a.submitted_at === null) return 1;
+        if (b.submitted_at === null) return -1;
+        return a.submitted_at.localeCompare(b.submitted_at);
+      });
+
+      const timeline: RunTimeline = {
+        run_id: run.id,
+        created_at: run.created_at,
+        compounds,
+      };
+      return timeline;
+    }),
+
+  /**
+   * Distribution of affinity and sample-0 metrics across the run's
+   * completed compounds, with a bucketCount-bin histogram per metric.
+   */
+  getStatistics: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        bucketCount: z.number().int().min(1).max(100).default(10),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const values: Record<string, number[]> = { binding_confidence: [], optimization_score: [] };
+      for (const field of NUMERIC_SAMPLE_METRICS) values[field] = [];
+
+      let completed = 0;
+      for (const c of run.compounds) {
+        if (c.status !== 'COMPLETED' || !c.metrics) continue;
+        completed++;
+        const affinity = c.metrics.affinity;
+        if (affinity) {
+          values.binding_confidence.push(affinity.binding_confidence);
+          values.optimization_score.push(affinity.optimization_score);
+        }
+        const sample = c.metrics.samples[0];
+        if (!sample) continue;
+        for (const field of NUMERIC_SAMPLE_METRICS) {
+          const v = sample[field];
+          if (v !== null) values[field].push(v);
+        }
+      }
+
+      const stats: RunStatistics = {
+        run_id: run.id,
+        completed_count: completed,
+        bucket_count: input.bucketCount,
+        metrics: Object.fromEntries(
+          Object.entries(values).map(([k, v]) => [k, computeMetricStatistics(v, input.bucketCount)]),
+        ),
+      };
+      return stats;
+    }),
+
+  /** Pearson correlation between two metrics across the run's completed compounds */
+  getMetricsCorrelation: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        metricA: metricFieldSchema,
+        metricB: metricFieldSchema,
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const xs: number[] = [];
+      const ys: number[] = [];
+      for (const c of run.compounds) {
+        if (c.status !== 'COMPLETED') continue;
+        const a = metricValue(c, input.metricA);
+        const b = metricValue(c, input.metricB);
+        if (a === null || b === null) continue;
+        xs.push(a);
+        ys.push(b);
+      }
+      if (xs.length < MIN_CORRELATION_POINTS) {
+        throw new Error(
+          `Need at least ${MIN_CORRELATION_POINTS} compounds with both metrics (have ${xs.length})`,
+        );
+      }
+
+      const r = pearson(xs, ys);
+      if (Number.isNaN(r)) throw new Error('One of the metrics has the same value for every compound');
+
+      const result: CorrelationResult = {
+        r,
+        n: xs.length,
+        p_value_approx: correlationPValue(r, xs.length),
+      };
+      return result;
+    }),
+
+  /**
+   * Heuristic next steps from ring cores shared by the run's best binders
+   * (see models/cheminfo). Hints are starting points, not predictions.
+   */
+  suggestNextCompounds: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        topN: z.number().int().min(1).max(50).default(5),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const run = ctx.services.state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      const top = run.compounds
+        .filter((c) => c.status === 'COMPLETED' && c.metrics?.affinity && c.smiles)
+        .sort(
+          (a, b) => b.metrics!.affinity!.binding_confidence - a.metrics!.affinity!.binding_confidence,
+        )
+        .slice(0, input.topN);
+      if (top.length === 0) throw new Error('No completed compounds with affinity metrics');
+
+      // core name → top compounds containing it, best first
+      const cores = new Map<string, Compound[]>();
+      for (const compound of top) {
+        let names: string[];
+        try {
+          names = findRingSystems(compound.smiles).map((s) => s.name);
+        } catch (e) {
+          console.warn(`Skipping unparseable SMILES for ${compound.display_name}:`, e);
+          continue;
+        }
+        for (const name of new Set(names)) {
+          cores.set(name, [...(cores.get(name) ?? []), compound]);
+        }
+      }
+
+      const hints: SuggestionHint[] = [...cores.entries()]
+        .filter(([, compounds]) => compounds.length >= 2)
+        .sort((a, b) => b[1].length - a[1].length)
+        .map(([name, compounds]) => ({
+          base_smiles: compounds[0].smiles,
+          reason:
+            `Top ${compounds.length} of ${top.length} hits share a ${name} core; ` +
+            `try varying the substituents around it`,
+        }));
+
+      if (hints.length === 0) {
+        const best = top[0];
+        const bestCores = [...cores.entries()]
+          .filter(([, compounds]) => compounds.includes(best))
+          .map(([name]) => name);
+        hints.push({
+          base_smiles: best.smiles,
+          reason: bestCores.length > 0
+            ? `No core is shared by the top hits; "${best.display_name}" (${bestCores.join(', ')}) scored best, so explore its close analogs`
+            : `No ring cores among the top hits; "${best.display_name}" scored best — consider rigidifying it with a ring`,
+        });
+      }
+      return hints;
+    }),
+
+  validateParams: publicProcedure
+    .input(z.object({ params: runParamsSchema }))
+    .query(({ input }) => {
+      const result: ParamsValidationResult = { valid: true, error: null };
+      try {
+        validateRunParams(input.params);
+      } catch (e) {
+        result.valid = false;
+        result.error = e instanceof Error ? e.message : String(e);
+      }
+      return result;
+    }),
+
+  /** Approximate GPU time for a run before creating it. No API call is made. */
+  estimateCost: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        compounds: z.array(compoundInputSchema),
+        params: runParamsSchema,
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const campaign = ctx.services.state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+      validateRunParams(input.params);
+
+      const targetLength = campaign.target_sequence.length;
+      const gpuSecs = input.compounds.reduce((total, c) => {
+        const smiles = input.params.mode === 'protein_only' ? [] : [c.smiles];
+        return total + estimateGpuSeconds(targetLength, smiles, input.params);
+      }, 0);
+
+      const estimate: SubmissionEstimate = {
+        compound_count: input.compounds.length,
+        estimated_gpu_hours: gpuSecs / 3600,
+        estimated_credits: null,
+      };
+      return estimate;
+    }),
+
+  /**
+   * Everything create checks, without an API call or any writes: the run
+   * folder name, each compound's folder name, and which compounds would be
+   * rejected (bad SMILES, or duplicates unless allowDuplicates).
+   */
+  createDryRun: publicProcedure
     .input(
       z.object({
         campaignId: z.string().uuid(),
         displayName: z.string().min(1),
-        compounds: z.array(compoundInputSchema).min(1),
+        compounds: z.array(compoundInputSchema).default([]),
+        compoundsMulti: z.array(multiLigandCompoundSchema).default([]),
         params: runParamsSchema,
+        allowDuplicates: z.boolean().default(false),
       }),
     )
+    .query(({ ctx, input }) => {
+      const campaign = ctx.services.state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
+      validateRunParams(input.params);
+
+      const preview: RunPreview = {
+        folder_name: uniqueFolderName(
+          sanitiseFolderName(input.displayName),
+          campaign.runs.map((r) => r.folder_name),
+        ),
+        compound_count: input.compounds.length + input.compoundsMulti.length,
+        valid_compounds: [],
+        invalid_compounds: [],
+        estimated_credits: null,
+      };
+
+      // Same duplicate rule as create: single-ligand compounds in protein_ligand runs
+      const checkDuplicates = !input.allowDuplicates && input.params.mode === 'protein_ligand';
+      const accepted: CompoundInput[] = [];
+      const acceptedSingle: { display_name: string; smiles: string }[] = [];
+      for (const c of input.compounds) {
+        const error = compoundInputError(c, input.params.mode);
+        if (error) {
+          preview.invalid_compounds.push({ name: c.name, error });
+          continue;
+        }
+        const duplicate = checkDuplicates ? findDuplicateSmiles(acceptedSingle, [c])[0] : undefined;
+        if (duplicate) {
+          preview.invalid_compounds.push({
+            name: c.name,
+            error: `Duplicate SMILES: repeats "${duplicate.first}"`,
+          });
+          continue;
+        }
+        accepted.push(c);
+        acceptedSingle.push({ display_name: c.name, smiles: c.smiles });
+      }
+      for (const c of input.compoundsMulti) {
+        const compoundInput: CompoundInput = {
+          name: c.name,
+          smiles: c.ligands[0].smiles,
+          ligands: c.ligands,
+        };
+        const error = compoundInputError(compoundInput, input.params.mode);
+        if (error) {
+          preview.invalid_compounds.push({ name: c.name, error });
+        } else {
+          accepted.push(compoundInput);
+        }
+      }
+
+      for (const compound of buildCompounds(accepted, [], campaign, input.params)) {
+        preview.valid_compounds.push({
+          name: compound.display_name,
+          folder_name: compound.folder_name,
+          smiles_list: compound.smiles_list,
+        });
+      }
+      return preview;
+    }),
+
+  create: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        displayName: z.string().min(1),
+        compounds: z.array(compoundInputSchema).default([]),
+        // Compounds with several ligands (one chain each) in one prediction
+        compoundsMulti: z.array(multiLigandCompoundSchema).default([]),
+        params: runParamsSchema,
+        allowDuplicates: z.boolean().default(false),
+        reuseExisting: z.boolean().default(false),
+      }).refine(
+        (val) => val.compounds.length + val.compoundsMulti.length > 0,
+        'A run needs at least one compound',
+      ),
+    )
     .mutation(async ({ ctx, input }) => {
-      const { state, client, eventBus } = ctx.services;
+      const { state, client } = ctx.services;
+      validateRunParams(input.params);
+      const inputs: CompoundInput[] = [
+        ...input.compounds,
+        ...input.compoundsMulti.map((c) => ({
+          name: c.name,
+          smiles: c.ligands[0].smiles,
+          ligands: c.ligands,
+        })),
+      ];
+
       const campaign = state.findCampaign(input.campaignId);
       if (!campaign) throw new Error('Campaign not found');
 
-      const apiKey = state.data.api_key;
+      const apiKey = state.activeApiKey();
       if (!apiKey) throw new Error('No API key configured');
 
-      // Pre-flight: verify API key before creating the run
-      try {
-        await client.testConnection(apiKey);
-      } catch (e) {
-        if (e instanceof BoltzApiError) {
-          if (e.statusCode === 401 || e.statusCode === 403) {
-            throw new Error('API key is invalid or expired. Check Settings.', { cause: e });
-          }
-          if (e.statusCode === 429) {
-            throw new Error('Boltz API rate limit reached. Try again in a few minutes.', { cause: e });
-          }
+      validateCompoundInputs(inputs, input.params.mode);
+
+      // Identical ligands waste API quota — require an explicit opt-in
+      // (single-ligand compounds only; multi-ligand sets are rarely repeated)
+      if (!input.allowDuplicates && input.params.mode === 'protein_ligand') {
+        const duplicates = findDuplicateSmiles([], input.compounds);
+        if (duplicates.length > 0) {
+          const shown = duplicates
+            .slice(0, 5)
+            .map((d) => `"${d.second}" repeats "${d.first}"`)
+            .join('; ');
+          const more = duplicates.length > 5 ? ` (and ${duplicates.length - 5} more)` : '';
+          throw new Error(`Duplicate SMILES in run: ${shown}${more}`);
         }
-        throw new Error('Cannot reach the Boltz API. Try again in a few minutes.', { cause: e });
       }
 
-      const targetSequence = campaign.target_sequence;
+      // Pre-flight: verify API key before creating the run
+      await verifyApiKey(client, apiKey);
 
       // Generate unique run folder name
       const runBase = sanitiseFolderName(input.displayName);
@@ -88,25 +646,45 @@ export const runsRouter = router({
       const runFolder = uniqueFolderName(runBase, existingRunFolders);
 
       // Build compound structs with unique folder names
-      const compoundFolders: string[] = [];
-      const compounds: Compound[] = input.compounds.map((c) => {
-        const base = sanitiseFolderName(c.name);
-        const folder = uniqueFolderName(base, compoundFolders);
-        compoundFolders.push(folder);
-        return {
-          id: uuidv4(),
-          display_name: c.name,
-          folder_name: folder,
-          smiles: c.smiles,
-          boltz_job_id: null,
-          status: 'PENDING' as JobStatus,
-          submitted_at: null,
-          completed_at: null,
-          metrics: null,
-          error_message: null,
-          download_error: null,
-        };
-      });
+      const compounds = buildCompounds(inputs, [], campaign, input.params);
+
+      // Reuse finished predictions with identical inputs instead of resubmitting
+      const reused = new Set<string>();
+      if (input.reuseExisting) {
+        for (const compound of compounds) {
+          const match = state.findMatchingPrediction(compound.input_hash!);
+          if (!match) continue;
+          const [srcCampaign, srcRun, src] = match;
+          const srcDir = path.join(
+            state.rootDir,
+            srcCampaign.folder_name,
+            srcRun.folder_name,
+            src.folder_name,
+          );
+          const destDir = path.join(
+            state.rootDir,
+            campaign.folder_name,
+            runFolder,
+            compound.folder_name,
+          );
+          try {
+            fs.cpSync(srcDir, destDir, { recursive: true });
+          } catch (e) {
+            console.warn(`Not reusing ${src.id}, copy failed:`, e);
+            continue;
+          }
+
+          compound.status = 'COMPLETED';
+          compound.boltz_job_id = src.boltz_job_id;
+          compound.submitted_at = src.submitted_at;
+          compound.completed_at = src.completed_at;
+          compound.metrics = structuredClone(src.metrics);
+          compound.download_checksum = src.download_checksum;
+          compound.cif_checksum = src.cif_checksum;
+          reused.add(compound.id);
+        }
+      }
+      const toSubmit = compounds.filter((c) => !reused.has(c.id));
 
       const run: Run = {
         id: uuidv4(),
@@ -115,116 +693,253 @@ export const runsRouter = router({
         archived: false,
         archived_at: null,
         params: input.params,
+        note: null,
+        paused: false,
         created_at: new Date().toISOString(),
         completed_at: null,
+        display_order: nextDisplayOrder(campaign.runs),
         compounds,
+        compacted: null,
       };
 
       // Save to state and create folder
       campaign.runs.push(run);
-      state.markDirty();
+      state.audit('create', 'run', run.id, `${compounds.length} compounds`);
       createRunFolder(state.rootDir, campaign.folder_name, runFolder);
       persistState(state.rootDir, state.data);
 
       try { trackEvent('run_submitted', { num_compounds: compounds.length }); } catch { /* telemetry must not abort mutations */ }
 
+      // Every compound reused: the run is already complete
+      if (toSubmit.length === 0) {
+        const runEvent = state.checkRunCompletion(run.id);
+        if (runEvent) ctx.services.eventBus.emit('run-completed', runEvent);
+        persistState(state.rootDir, state.data);
+      }
+
       // Return run snapshot immediately, then submit compounds in background
       const runSnapshot = structuredClone(run);
 
-      // Background: submit all compounds with bounded concurrency
-      const limit = pLimit(SUBMIT_CONCURRENCY);
-      const runId = run.id;
-      const campaignId = input.campaignId;
+      // Fire and forget — persists after all submissions complete
+      if (toSubmit.length > 0) {
+        submitCompounds(ctx.services, apiKey, campaign, run, toSubmit).catch((err) => {
+          console.error(`Submission failed for run ${run.id}:`, err);
+        });
+      }
+
+      return runSnapshot;
+    }),
+
+  /** Create a run from a saved template's params and compounds, with fresh IDs. */
+  createFromTemplate: publicProcedure
+    .input(
+      z.object({
+        campaignId: z.string().uuid(),
+        templateId: z.string().uuid(),
+        displayName: z.string().min(1),
+      }),
+    )
+    .mutation(async ({ ctx, input }) => {
+      const { state, client } = ctx.services;
+      const template = state.findTemplate(input.templateId);
+      if (!template) throw new Error('Template not found');
+      if (template.compounds.length === 0) throw new Error('Template has no compounds');
 
-      // Shared rate-limit gate: when any submission gets a 429,
-      // pause all queued submissions for the Retry-After duration.
-      const sleep = (ms: number) => new Promise<void>((r) => setTimeout(r, ms));
-      let pauseUntil = 0;
+      const campaign = state.findCampaign(input.campaignId);
+      if (!campaign) throw new Error('Campaign not found');
 
-      function triggerPause(delayMs: number): void {
-        pauseUntil = Math.max(pauseUntil, Date.now() + delayMs);
-      }
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
 
-      async function waitForGate(): Promise<void> {
-        let remaining = pauseUntil - Date.now();
-        while (remaining > 0) {
-          await sleep(remaining);
-          remaining = pauseUntil - Date.now();
-        }
-      }
+      const params = structuredClone(template.params);
+      validateRunParams(params);
+      const inputs: CompoundInput[] = template.compounds.map((c) => ({ ...c }));
+      validateCompoundInputs(inputs, params.mode);
 
-      const tasks = compounds.map((compound) =>
-        limit(async () => {
-          await waitForGate();
-          const inferenceInput = buildInferenceInput(targetSequence, compound.smiles, campaign.target_type);
-          const inferenceOptions = buildInferenceOptions(input.params);
-          const now = new Date().toISOString();
+      await verifyApiKey(client, apiKey);
 
-          try {
-            const predictionName = buildPredictionName(
-              campaign.display_name,
-              run.display_name,
-              compound.display_name,
-            );
-            const resp = await client.submitPrediction(
-              apiKey,
-              inferenceInput,
-              inferenceOptions,
-              predictionName,
-              { onRateLimited: triggerPause },
-            );
-
-            // Update compound state
-            const liveCompound = state.findCompound(compound.id);
-            if (liveCompound) {
-              liveCompound.boltz_job_id = resp.prediction_id;
-              liveCompound.status = 'CREATED';
-              liveCompound.submitted_at = now;
-            }
-            state.markDirty();
-
-            const evt: CompoundStatusEvent = {
-              compound_id: compound.id,
-              run_id: runId,
-              campaign_id: campaignId,
-              status: 'CREATED',
-              metrics: null,
-              completed_at: null,
-            };
-            eventBus.emit('compound-status-changed', evt);
-          } catch (e) {
-            const msg = humanizeError(e);
-            console.error(`Failed to submit compound ${compound.id}:`, e);
-
-            const liveCompound = state.findCompound(compound.id);
-            if (liveCompound) {
-              liveCompound.status = 'FAILED';
-              liveCompound.completed_at = now;
-              liveCompound.error_message = msg;
-            }
-            state.markDirty();
-
-            const evt: CompoundStatusEvent = {
-              compound_id: compound.id,
-              run_id: runId,
-              campaign_id: campaignId,
-              status: 'FAILED',
-              metrics: null,
-              completed_at: now,
-            };
-            eventBus.emit('compound-status-changed', evt);
-          }
-        }),
+      const runFolder = uniqueFolderName(
+        sanitiseFolderName(input.displayName),
+        campaign.runs.map((r) => r.folder_name),
       );
+      const compounds = buildCompounds(inputs, [], campaign, params);
 
-      // Fire and forget — persist after all submissions complete
-      Promise.allSettled(tasks).then(() => {
-        persistState(state.rootDir, state.data);
+      const run: Run = {
+        id: uuidv4(),
+        display_name: input.displayName,
+        folder_name: runFolder,
+        archived: false,
+        archived_at: null,
+        params,
+        note: null,
+        paused: false,
+        created_at: new Date().toISOString(),
+        completed_at: null,
+        display_order: nextDisplayOrder(campaign.runs),
+        compounds,
+        compacted: null,
+      };
+
+      campaign.runs.push(run);
+      state.audit('create', 'run', run.id, `${compounds.length} compounds from template "${template.name}"`);
+      createRunFolder(state.rootDir, campaign.folder_name, runFolder);
+      persistState(state.rootDir, state.data);
+
+      try { trackEvent('run_submitted', { num_compounds: compounds.length }); } catch { /* telemetry must not abort mutations */ }
+
+      const runSnapshot = structuredClone(run);
+
+      // Fire and forget — persists after all submissions complete
+      submitCompounds(ctx.services, apiKey, campaign, run, compounds).catch((err) => {
+        console.error(`Submission failed for run ${run.id}:`, err);
       });
 
       return runSnapshot;
     }),
 
+  importCsv: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        csvText: z.string().min(1),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
+      if (run.archived) throw new Error('Cannot import compounds into an archived run');
+      if (run.completed_at) throw new Error('Cannot import compounds into a completed run');
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      const parsed = Papa.parse<Record<string, string>>(input.csvText.trim(), {
+        header: true,
+        skipEmptyLines: true,
+        transformHeader: (h) => h.trim().toLowerCase(),
+      });
+      const fields = parsed.meta.fields ?? [];
+      if (!fields.includes('name') || !fields.includes('smiles')) {
+        throw new Error('CSV must have "name" and "smiles" columns');
+      }
+
+      // Skip incomplete rows with a warning rather than failing the import
+      const warnings: string[] = [];
+      const inputs: CompoundInput[] = [];
+      parsed.data.forEach((row, i) => {
+        const line = i + 2; // 1-based, after the header
+        const name = (row.name ?? '').trim();
+        const smiles = (row.smiles ?? '').trim();
+        if (!name) {
+          warnings.push(`Row ${line}: blank name, skipped`);
+        } else if (!smiles) {
+          warnings.push(`Row ${line} (${name}): blank SMILES, skipped`);
+        } else {
+          inputs.push({ name, smiles });
+        }
+      });
+
+      if (inputs.length === 0) {
+        throw new Error('No valid compounds found in CSV');
+      }
+      validateCompoundInputs(inputs, run.params.mode);
+
+      return appendCompounds(ctx.services, apiKey, campaign, run, inputs, warnings, 'import_csv');
+    }),
+
+  importSdf: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        sdfPath: z.string().min(1),
+      }),
+    )
+    .mutation(async ({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (!path.isAbsolute(input.sdfPath)) throw new Error('SDF path must be absolute');
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
+      if (run.archived) throw new Error('Cannot import compounds into an archived run');
+      if (run.completed_at) throw new Error('Cannot import compounds into a completed run');
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      const records = parseSdf(await fs.promises.readFile(input.sdfPath, 'utf-8'));
+
+      // Skip records we can't use with a warning rather than failing the import
+      const warnings: string[] = [];
+      const inputs: CompoundInput[] = [];
+      for (const record of records) {
+        if (!record.name) {
+          warnings.push(`Record ${record.index}: blank molecule name, skipped`);
+        } else if (!record.smiles) {
+          warnings.push(`Record ${record.index} (${record.name}): no SMILES field, skipped`);
+        } else {
+          inputs.push({ name: record.name, smiles: record.smiles });
+        }
+      }
+
+      if (inputs.length === 0) {
+        throw new Error('No valid compounds found in SDF');
+      }
+      validateCompoundInputs(inputs, run.params.mode);
+
+      return appendCompounds(ctx.services, apiKey, campaign, run, inputs, warnings, 'import_sdf');
+    }),
+
+  /**
+   * Write the ligand pose (chain B) of every completed compound's sample
+   * into one SD file at destPath. Compounds whose CIF is missing or has no
+   * ligand atoms are skipped. Returns the number of molecules written.
+   */
+  exportSdf: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        sampleIndex: z.number().int().min(0).default(0),
+        destPath: z.string().min(1),
+      }),
+    )
+    .mutation(async ({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (!path.isAbsolute(input.destPath)) throw new Error('Export path must be absolute');
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
+
+      const records: { name: string; atoms: SdfAtom[]; smiles?: string }[] = [];
+      for (const compound of run.compounds) {
+        if (compound.status !== 'COMPLETED') continue;
+        const cifPath = path.join(
+          state.rootDir,
+          campaign.folder_name,
+          run.folder_name,
+          compound.folder_name,
+          `sample_${input.sampleIndex}_structure.cif`,
+        );
+        try {
+          const atoms = parseCifAtoms(await fs.promises.readFile(cifPath, 'utf-8')).filter(
+            (a) => a.chain === DEFAULT_LIGAND_CHAIN,
+          );
+          if (atoms.length === 0) {
+            console.warn(`No ligand atoms in ${cifPath}, skipping`);
+            continue;
+          }
+          records.push({ name: compound.display_name, atoms, smiles: compound.smiles || undefined });
+        } catch (e) {
+          console.warn(`Skipping ${compound.display_name} in SDF export:`, e);
+        }
+      }
+
+      if (records.length === 0) throw new Error('No completed compounds with ligand structures');
+      await fs.promises.writeFile(input.destPath, writeSdf(records), 'utf-8');
+      return records.length;
+    }),
+
   rename: publicProcedure
     .input(
       z.object({
@@ -234,11 +949,9 @@ export const runsRouter = router({
     )
     .mutation(({ ctx, input }) => {
       const { state } = ctx.services;
-      const campaign = state.findCampaignForRun(input.runId);
-      if (!campaign) throw new Error('Run not found');
-
-      const run = campaign.runs.find((r) => r.id === input.runId);
-      if (!run) throw new Error('Run not found');
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
 
       const oldFolder = run.folder_name;
       const newBase = sanitiseFolderName(input.newName);
@@ -258,7 +971,24 @@ export const runsRouter = router({
       // Update state after disk success
       run.display_name = input.newName;
       run.folder_name = newFolder;
-      state.markDirty();
+      state.audit('rename', 'run', run.id, input.newName);
+      persistState(state.rootDir, state.data);
+    }),
+
+  setNote: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        note: z.string().nullable(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const run = state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      run.note = input.note;
+      state.audit('set_note', 'run', run.id);
       persistState(state.rootDir, state.data);
     }),
 
@@ -270,7 +1000,14 @@ export const runsRouter = router({
 
       run.archived = true;
       run.archived_at = new Date().toISOString();
-      ctx.services.state.markDirty();
+      ctx.services.state.audit('archive', 'run', run.id);
+
+      const campaign = ctx.services.state.findCampaignForRun(run.id)!;
+      ctx.services.eventBus.emit('run-archived', {
+        run_id: run.id,
+        campaign_id: campaign.id,
+        archived_at: run.archived_at,
+      } satisfies RunArchivedEvent);
     }),
 
   unarchive: publicProcedure
@@ -281,26 +1018,105 @@ export const runsRouter = router({
 
       run.archived = false;
       run.archived_at = null;
-      ctx.services.state.markDirty();
+      ctx.services.state.audit('unarchive', 'run', run.id);
     }),
 
-  cancel: publicProcedure
+  pause: publicProcedure
+    .input(z.object({ runId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const run = state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+
+      run.paused = true;
+      state.audit('pause', 'run', run.id);
+      persistState(state.rootDir, state.data);
+    }),
+
+  resume: publicProcedure
+    .input(z.object({ runId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
+
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      run.paused = false;
+      state.audit('resume', 'run', run.id);
+      persistState(state.rootDir, state.data);
+
+      const pending = run.compounds.filter((c) => c.status === 'PENDING');
+      if (pending.length > 0) {
+        // Fire and forget — persists after all submissions complete
+        submitCompounds(ctx.services, apiKey, campaign, run, pending).catch((err) => {
+          console.error(`Submission failed for run ${run.id}:`, err);
+        });
+      }
+      return { resubmitted: pending.length };
+    }),
+
+  /**
+   * Cancel only the compounds still waiting to be submitted, keeping the
+   * ones already on the API. Returns the number cancelled.
+   */
+  cancelPending: publicProcedure
     .input(z.object({ runId: z.string().uuid() }))
     .mutation(({ ctx, input }) => {
       const { state, eventBus } = ctx.services;
-      const campaign = state.findCampaignForRun(input.runId);
-      if (!campaign) throw new Error('Run not found');
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
 
-      const run = campaign.runs.find((r) => r.id === input.runId);
-      if (!run) throw new Error('Run not found');
+      const now = new Date().toISOString();
+      const events: CompoundStatusEvent[] = [];
+      for (const compound of run.compounds) {
+        if (compound.status !== 'PENDING' || compound.boltz_job_id) continue;
+        compound.status = 'CANCELLED';
+        compound.completed_at = now;
+        events.push({
+          compound_id: compound.id,
+          run_id: run.id,
+          campaign_id: campaign.id,
+          status: 'CANCELLED',
+          metrics: null,
+          completed_at: now,
+          download_error: compound.download_error,
+        });
+      }
+      if (events.length === 0) return 0;
+
+      state.audit('cancel', 'run', run.id, `${events.length} pending compounds`);
+      const runEvent = state.checkRunCompletion(run.id);
+      for (const evt of events) {
+        eventBus.emit('compound-status-changed', evt);
+      }
+      if (runEvent) {
+        eventBus.emit('run-completed', runEvent);
+      }
+      persistState(state.rootDir, state.data);
+      return events.length;
+    }),
+
+  cancel: publicProcedure
+    .input(z.object({ runId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+      const context = state.findRunContext(input.runId);
+      if (!context) throw new Error('Run not found');
+      const [campaign, run] = context;
 
       const now = new Date().toISOString();
       const events: CompoundStatusEvent[] = [];
+      const jobIds: string[] = [];
 
       for (const compound of run.compounds) {
         if (!isTerminal(compound.status)) {
           compound.status = 'CANCELLED';
           compound.completed_at = now;
+          if (compound.boltz_job_id) jobIds.push(compound.boltz_job_id);
 
           events.push({
             compound_id: compound.id,
@@ -309,12 +1125,13 @@ export const runsRouter = router({
             status: 'CANCELLED',
             metrics: null,
             completed_at: now,
+            download_error: compound.download_error,
           });
         }
       }
 
       if (events.length > 0) {
-        state.markDirty();
+        state.audit('cancel', 'run', run.id, `${events.length} compounds`);
 
         // Check run completion
         const runEvent = state.checkRunCompletion(input.runId);
@@ -328,6 +1145,19 @@ export const runsRouter = router({
         }
 
         persistState(state.rootDir, state.data);
+        cancelRemotePredictions(ctx.services, jobIds);
       }
     }),
+
+  // ── Subscriptions ──────────────────────────────────────────────────
+
+  onArchived: publicProcedure.subscription(({ ctx }) => {
+    return observable<RunArchivedEvent>((emit) => {
+      const handler = (event: RunArchivedEvent) => emit.next(event);
+      ctx.services.eventBus.on('run-archived', handler);
+      return () => {
+        ctx.services.eventBus.off('run-archived', handler);
+      };
+    });
+  }),
 });
@@ -1,18 +1,106 @@
 import { z } from 'zod';
 import { dialog } from 'electron';
+import { v4 as uuidv4 } from 'uuid';
 import { router, publicProcedure } from '../trpc';
-import { writeRootDir, readAnalyticsEnabled, writeAnalyticsEnabled } from '../../services/prefs';
+import { observable } from '@trpc/server/observable';
+import {
+  readRecentWorkspaces,
+  readAnalyticsEnabled,
+  writeAnalyticsEnabled,
+  readPollIntervalSecs,
+  writePollIntervalSecs,
+  readSubmissionConcurrency,
+  writeSubmissionConcurrency,
+  readCompressState,
+  writeCompressState,
+  readRunParamsPresets,
+  saveRunParamsPreset,
+  deleteRunParamsPreset,
+  writeDefaultRunParamsPreset,
+  readDefaultRunParams,
+  readExternalViewerPath,
+  writeExternalViewerPath,
+  readAutoArchiveDays,
+  writeAutoArchiveDays,
+  readProxyUrl,
+  writeProxyUrl,
+  resolveProxyUrl,
+} from '../../services/prefs';
+import { BoltzClient, validateProxyUrl } from '../../services/boltz-client';
 import { setTelemetryEnabled } from '../../services/telemetry';
-import { loadState, persistState } from '../../services/storage';
+import { humanizeError } from '../../services/humanize-error';
+import { persistState, configureCompression } from '../../services/storage';
+import type {
+  ApiKeyEntry,
+  ConnectionInfo,
+  PollConfigChangedEvent,
+  WorkspaceSwitchedEvent,
+} from '../../models/types';
+import type { AppState } from '../../models/state';
+import {
+  MIN_POLL_INTERVAL_SECS,
+  MAX_POLL_INTERVAL_SECS,
+  MIN_SUBMIT_CONCURRENCY,
+  MAX_SUBMIT_CONCURRENCY,
+  MAX_AUTO_ARCHIVE_DAYS,
+  QUOTA_CACHE_MS,
+} from '../../models/types';
+import { validateRunParams } from '../../models/validation';
+import { runParamsSchema } from '../schemas';
 import fs from 'node:fs';
 import path from 'node:path';
 
+/** Validate a workspace path and create it if missing. */
+function prepareRootDir(rootDir: string): void {
+  if (!path.isAbsolute(rootDir)) {
+    throw new Error('Workspace directory must be an absolute path');
+  }
+  if (fs.existsSync(rootDir) && !fs.statSync(rootDir).isDirectory()) {
+    throw new Error('Workspace path exists but is not a directory');
+  }
+  fs.mkdirSync(rootDir, { recursive: true });
+}
+
+/**
+ * Backward-compatible single-key update: replace the default entry's key,
+ * creating a "Default" entry if needed, or remove it when key is null.
+ */
+function setDefaultApiKey(state: AppState, key: string | null): void {
+  const keys = state.data.api_keys;
+  const idx = keys.findIndex((k) => k.is_default);
+  if (key === null) {
+    if (idx !== -1) removeApiKey(state, keys[idx].id);
+    return;
+  }
+  if (idx !== -1) {
+    keys[idx].key = key;
+    return;
+  }
+  const entry: ApiKeyEntry = { id: uuidv4(), label: 'Default', key, is_default: true };
+  keys.push(entry);
+  state.data.active_api_key_id ??= entry.id;
+}
+
+/** Remove an entry, promoting the next key to default/active if needed. */
+function removeApiKey(state: AppState, id: string): void {
+  const keys = state.data.api_keys;
+  const idx = keys.findIndex((k) => k.id === id);
+  if (idx === -1) throw new Error('API key not found');
+
+  const [removed] = keys.splice(idx, 1);
+  if (removed.is_default && keys.length > 0) keys[0].is_default = true;
+  if (state.data.active_api_key_id === removed.id) {
+    state.data.active_api_key_id = keys.find((k) => k.is_default)?.id ?? null;
+  }
+}
+
 export const settingsRouter = router({
   get: publicProcedure.query(({ ctx }) => {
     const { state } = ctx.services;
     return {
-      api_key: state.data.api_key,
+      api_key: state.activeApiKey(),
       root_dir: state.rootDir,
+      proxy_url: readProxyUrl(),
     };
   }),
 
@@ -21,43 +109,105 @@ export const settingsRouter = router({
       z.object({
         apiKey: z.string().nullable().optional(),
         rootDir: z.string().nullable().optional(),
+        proxyUrl: z.string().min(1).nullable().optional(),
       }),
     )
-    .mutation(({ ctx, input }) => {
-      const { state } = ctx.services;
+    .mutation(async ({ ctx, input }) => {
+      const { state, client } = ctx.services;
 
-      // Validate root dir if provided
+      // Validate and create the new root before touching state
       if (input.rootDir) {
-        if (!path.isAbsolute(input.rootDir)) {
-          throw new Error('Workspace directory must be an absolute path');
-        }
-        if (fs.existsSync(input.rootDir) && !fs.statSync(input.rootDir).isDirectory()) {
-          throw new Error('Workspace path exists but is not a directory');
-        }
+        prepareRootDir(input.rootDir);
       }
 
-      // Create directory and persist prefs before updating state
-      if (input.rootDir) {
-        fs.mkdirSync(input.rootDir, { recursive: true });
-        writeRootDir(input.rootDir);
+      if (input.proxyUrl !== undefined) {
+        if (input.proxyUrl) validateProxyUrl(input.proxyUrl);
+        writeProxyUrl(input.proxyUrl);
+        client.setProxy(resolveProxyUrl()); // env vars still take precedence
       }
 
       // Update state
       if (input.apiKey !== undefined) {
-        state.data.api_key = input.apiKey ?? null;
+        setDefaultApiKey(state, input.apiKey || null);
       }
       if (input.rootDir) {
-        // Reload state from new root dir, preserving the API key
-        const apiKey = state.data.api_key;
-        const newState = loadState(input.rootDir);
-        state.data = newState.data;
-        state.data.api_key = apiKey;
-        state.rootDir = input.rootDir;
+        // Reload state from new root dir, preserving the API keys
+        await ctx.services.switchWorkspace(input.rootDir);
+      }
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+    }),
+
+  listApiKeys: publicProcedure.query(({ ctx }) => {
+    const { state } = ctx.services;
+    return {
+      keys: state.data.api_keys,
+      active_id: state.activeApiKeyEntry()?.id ?? null,
+    };
+  }),
+
+  addApiKey: publicProcedure
+    .input(
+      z.object({
+        label: z.string().trim().min(1),
+        key: z.string().min(1),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (state.data.api_keys.some((k) => k.label === input.label)) {
+        throw new Error(`An API key labelled "${input.label}" already exists`);
+      }
+
+      // The first key becomes the default and active key
+      const first = state.data.api_keys.length === 0;
+      const entry: ApiKeyEntry = {
+        id: uuidv4(),
+        label: input.label,
+        key: input.key,
+        is_default: first,
+      };
+      state.data.api_keys.push(entry);
+      if (first) state.data.active_api_key_id = entry.id;
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+      return entry;
+    }),
+
+  setActiveApiKey: publicProcedure
+    .input(z.object({ id: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (!state.data.api_keys.some((k) => k.id === input.id)) {
+        throw new Error('API key not found');
       }
+
+      state.data.active_api_key_id = input.id;
+      state.quota = null; // belongs to the previous key
       state.markDirty();
       persistState(state.rootDir, state.data);
     }),
 
+  removeApiKey: publicProcedure
+    .input(z.object({ id: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      removeApiKey(state, input.id);
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+    }),
+
+  listWorkspaces: publicProcedure.query(() => {
+    return readRecentWorkspaces();
+  }),
+
+  switchWorkspace: publicProcedure
+    .input(z.object({ rootDir: z.string().min(1) }))
+    .mutation(async ({ ctx, input }) => {
+      prepareRootDir(input.rootDir);
+      await ctx.services.switchWorkspace(input.rootDir);
+    }),
+
   testConnection: publicProcedure
     .input(z.object({ apiKey: z.string().min(1) }))
     .mutation(async ({ ctx, input }) => {
@@ -70,6 +220,62 @@ export const settingsRouter = router({
       }
     }),
 
+  testConnectionVerbose: publicProcedure.mutation(async ({ ctx }) => {
+    const { state, client } = ctx.services;
+    const apiKey = state.activeApiKey();
+    if (!apiKey) throw new Error('No API key configured');
+
+    const start = performance.now();
+    const info: ConnectionInfo = { success: false, latency_ms: 0, api_version: null, error: null };
+    try {
+      const { apiVersion } = await client.probeConnection(apiKey);
+      info.success = true;
+      info.api_version = apiVersion;
+    } catch (e) {
+      info.error = humanizeError(e);
+    }
+    info.latency_ms = Math.round(performance.now() - start);
+    return info;
+  }),
+
+  /**
+   * Fetch the account's credit balance. Reuses the cached answer if it is
+   * younger than QUOTA_CACHE_MS unless force is set.
+   */
+  checkQuota: publicProcedure
+    .input(z.object({ force: z.boolean().default(false) }))
+    .mutation(async ({ ctx, input }) => {
+      const { state, client } = ctx.services;
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      const cached = state.quota;
+      if (!input.force && cached && Date.now() - Date.parse(cached.checked_at) < QUOTA_CACHE_MS) {
+        return cached;
+      }
+      state.quota = await client.getQuota(apiKey);
+      return state.quota;
+    }),
+
+  getCachedQuota: publicProcedure.query(({ ctx }) => {
+    return ctx.services.state.quota;
+  }),
+
+  testProxy: publicProcedure
+    .input(z.object({ proxyUrl: z.string().min(1) }))
+    .mutation(async ({ ctx, input }) => {
+      const apiKey = ctx.services.state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      const client = new BoltzClient(undefined, { proxyUrl: input.proxyUrl });
+      try {
+        await client.testConnection(apiKey);
+        return true;
+      } catch {
+        return false;
+      }
+    }),
+
   selectRootDir: publicProcedure.mutation(async () => {
     const result = await dialog.showOpenDialog({
       properties: ['openDirectory', 'createDirectory'],
@@ -91,4 +297,150 @@ export const settingsRouter = router({
       writeAnalyticsEnabled(input.enabled);
       setTelemetryEnabled(input.enabled);
     }),
+
+  getPollInterval: publicProcedure.query(() => {
+    return { interval_secs: readPollIntervalSecs() };
+  }),
+
+  updatePollInterval: publicProcedure
+    .input(
+      z.object({
+        intervalSecs: z.number().int().min(MIN_POLL_INTERVAL_SECS).max(MAX_POLL_INTERVAL_SECS),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      writePollIntervalSecs(input.intervalSecs);
+      ctx.services.poller.restart(input.intervalSecs * 1000);
+
+      ctx.services.eventBus.emit('poll-config-changed', {
+        interval_secs: input.intervalSecs,
+      } satisfies PollConfigChangedEvent);
+    }),
+
+  getSubmissionConcurrency: publicProcedure.query(() => {
+    return { concurrency: readSubmissionConcurrency() };
+  }),
+
+  updateSubmissionConcurrency: publicProcedure
+    .input(
+      z.object({
+        concurrency: z.number().int().min(MIN_SUBMIT_CONCURRENCY).max(MAX_SUBMIT_CONCURRENCY),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      writeSubmissionConcurrency(input.concurrency);
+      // Takes effect on the next submission batch
+      ctx.services.submitConcurrency = input.concurrency;
+    }),
+
+  getCompressState: publicProcedure.query(() => {
+    return { enabled: readCompressState() };
+  }),
+
+  setCompressState: publicProcedure
+    .input(z.object({ enabled: z.boolean() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      writeCompressState(input.enabled);
+      configureCompression(input.enabled);
+
+      // Write immediately so the new format becomes the newest state file
+      persistState(state.rootDir, state.data);
+    }),
+
+  listRunParamsPresets: publicProcedure.query(() => {
+    return readRunParamsPresets();
+  }),
+
+  saveRunParamsPreset: publicProcedure
+    .input(z.object({ name: z.string().trim().min(1), params: runParamsSchema }))
+    .mutation(({ input }) => {
+      validateRunParams(input.params);
+      saveRunParamsPreset({ name: input.name, params: input.params });
+    }),
+
+  deleteRunParamsPreset: publicProcedure
+    .input(z.object({ name: z.string().min(1) }))
+    .mutation(({ input }) => {
+      deleteRunParamsPreset(input.name);
+    }),
+
+  setDefaultRunParamsPreset: publicProcedure
+    .input(z.object({ name: z.string().min(1).nullable() }))
+    .mutation(({ input }) => {
+      if (input.name && !readRunParamsPresets().some((p) => p.name === input.name)) {
+        throw new Error('Preset not found');
+      }
+      writeDefaultRunParamsPreset(input.name);
+    }),
+
+  getDefaultRunParams: publicProcedure.query(() => {
+    return readDefaultRunParams();
+  }),
+
+  getAutoArchive: publicProcedure.query(() => {
+    return { after_days: readAutoArchiveDays() };
+  }),
+
+  /** Archive completed runs automatically after afterDays; null turns it off */
+  updateAutoArchive: publicProcedure
+    .input(
+      z.object({
+        afterDays: z.number().int().min(1).max(MAX_AUTO_ARCHIVE_DAYS).nullable(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      writeAutoArchiveDays(input.afterDays);
+      ctx.services.poller.setAutoArchiveDays(input.afterDays);
+    }),
+
+  getExternalViewer: publicProcedure.query(() => {
+    return { path: readExternalViewerPath() };
+  }),
+
+  configureExternalViewer: publicProcedure
+    .input(z.object({ viewerPath: z.string().min(1).nullable() }))
+    .mutation(({ input }) => {
+      if (input.viewerPath) {
+        const viewerPath = input.viewerPath;
+        if (!path.isAbsolute(viewerPath) || !fs.existsSync(viewerPath)) {
+          throw new Error('Viewer not found');
+        }
+        // macOS app bundles are directories launched via `open -a`
+        const isAppBundle =
+          process.platform === 'darwin' &&
+          viewerPath.endsWith('.app') &&
+          fs.statSync(viewerPath).isDirectory();
+        if (!isAppBundle) {
+          try {
+            fs.accessSync(viewerPath, fs.constants.X_OK);
+          } catch {
+            throw new Error('Viewer is not executable');
+          }
+        }
+      }
+      writeExternalViewerPath(input.viewerPath);
+    }),
+
+  // ── Subscriptions ──────────────────────────────────────────────────
+
+  onPollConfigChanged: publicProcedure.subscription(({ ctx }) => {
+    return observable<PollConfigChangedEvent>((emit) => {
+      const handler = (event: PollConfigChangedEvent) => emit.next(event);
+      ctx.services.eventBus.on('poll-config-changed', handler);
+      return () => {
+        ctx.services.eventBus.off('poll-config-changed', handler);
+      };
+    });
+  }),
+
+  onWorkspaceSwitched: publicProcedure.subscription(({ ctx }) => {
+    return observable<WorkspaceSwitchedEvent>((emit) => {
+      const handler = (event: WorkspaceSwitchedEvent) => emit.next(event);
+      ctx.services.eventBus.on('workspace-switched', handler);
+      return () => {
+        ctx.services.eventBus.off('workspace-switched', handler);
+      };
+    });
+  }),
 });
@@ -0,0 +1,98 @@
+import { z } from 'zod';
+import { v4 as uuidv4 } from 'uuid';
+import { router, publicProcedure } from '../trpc';
+import { persistState } from '../../services/storage';
+import type { CompoundTemplate, RunTemplate } from '../../models/types';
+import { DEFAULT_LIGAND_CHAIN } from '../../models/types';
+
+export const templatesRouter = router({
+  list: publicProcedure.query(({ ctx }) => {
+    return [...ctx.services.state.data.templates].sort((a, b) => a.name.localeCompare(b.name));
+  }),
+
+  get: publicProcedure
+    .input(z.object({ templateId: z.string().uuid() }))
+    .query(({ ctx, input }) => {
+      const template = ctx.services.state.findTemplate(input.templateId);
+      if (!template) throw new Error('Template not found');
+      return template;
+    }),
+
+  /** Capture a run's params and compound names/SMILES as a reusable template. */
+  saveFromRun: publicProcedure
+    .input(
+      z.object({
+        runId: z.string().uuid(),
+        name: z.string().trim().min(1),
+        description: z.string().nullable().default(null),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const run = state.findRun(input.runId);
+      if (!run) throw new Error('Run not found');
+      if (state.data.templates.some((t) => t.name === input.name)) {
+        throw new Error(`A template named "${input.name}" already exists`);
+      }
+
+      const compounds: CompoundTemplate[] = run.compounds.map((c) => {
+        const multi =
+          c.smiles_list.length > 1 ||
+          (c.smiles_list.length === 1 && c.smiles_list[0].chain_id !== DEFAULT_LIGAND_CHAIN);
+        return multi
+          ? { name: c.display_name, smiles: c.smiles, ligands: structuredClone(c.smiles_list) }
+          : { name: c.display_name, smiles: c.smiles };
+      });
+
+      const template: RunTemplate = {
+        id: uuidv4(),
+        name: input.name,
+        description: input.description,
+        params: structuredClone(run.params),
+        compounds,
+        created_at: new Date().toISOString(),
+      };
+      state.data.templates.push(template);
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+      return template;
+    }),
+
+  update: publicProcedure
+    .input(
+      z.object({
+        templateId: z.string().uuid(),
+        name: z.string().trim().min(1).optional(),
+        description: z.string().nullable().optional(),
+      }),
+    )
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const template = state.findTemplate(input.templateId);
+      if (!template) throw new Error('Template not found');
+
+      if (input.name !== undefined && input.name !== template.name) {
+        if (state.data.templates.some((t) => t.name === input.name)) {
+          throw new Error(`A template named "${input.name}" already exists`);
+        }
+        template.name = input.name;
+      }
+      if (input.description !== undefined) template.description = input.description;
+
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+      return template;
+    }),
+
+  delete: publicProcedure
+    .input(z.object({ templateId: z.string().uuid() }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const index = state.data.templates.findIndex((t) => t.id === input.templateId);
+      if (index === -1) throw new Error('Template not found');
+
+      state.data.templates.splice(index, 1);
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+    }),
+});
@@ -0,0 +1,466 @@
+import { z } from 'zod';
+import fs from 'node:fs';
+import path from 'node:path';
+import Papa from 'papaparse';
+import { observable } from '@trpc/server/observable';
+import { router, publicProcedure } from '../trpc';
+import type {
+  ActivityEvent,
+  AuditEntry,
+  CompoundWithContext,
+  DownloadQueueEntry,
+  DownloadQueueStatus,
+  FailedCompoundEntry,
+  GlobalStats,
+  JobStatus,
+  RepairReport,
+  SampleMetrics,
+  StateReloadedEvent,
+  StateRestoredEvent,
+} from '../../models/types';
+import {
+  ALL_JOB_STATUSES,
+  REMOTE_PREDICTIONS_PAGE_SIZE,
+  isTerminal,
+} from '../../models/types';
+import {
+  listBackups,
+  readBackup,
+  persistState,
+  validateWorkspace,
+  scanIncompleteDownloads,
+  computeWorkspaceSize,
+  scanOrphanedFolders,
+  deleteOrphanedFolder,
+} from '../../services/storage';
+import { checkIntegrity } from '../../models/state';
+import { buildHistogram, metricValue } from '../../models/stats';
+import { metricFieldSchema } from '../schemas';
+
+const SAMPLE_METRIC_FIELDS: (keyof SampleMetrics)[] = [
+  'structure_confidence',
+  'iptm',
+  'ligand_iptm',
+  'complex_plddt',
+  'ptm',
+  'protein_iptm',
+  'complex_iplddt',
+  'complex_pde',
+  'complex_ipde',
+  'chains_ptm',
+  'pair_chains_iptm',
+];
+
+export const workspaceRouter = router({
+  getStats: publicProcedure.query(({ ctx }) => {
+    const { campaigns } = ctx.services.state.data;
+
+    const byStatus = Object.fromEntries(
+      ALL_JOB_STATUSES.map((s) => [s, 0]),
+    ) as Record<JobStatus, number>;
+
+    const stats: GlobalStats = {
+      total_campaigns: campaigns.length,
+      active_campaigns: 0,
+      archived_campaigns: 0,
+      total_runs: 0,
+      active_runs: 0,
+      archived_runs: 0,
+      total_compounds: 0,
+      compounds_by_status: byStatus,
+      total_completed: 0,
+      success_rate: 0,
+    };
+
+    // Single pass over campaigns → runs → compounds
+    let terminalCount = 0;
+    for (const campaign of campaigns) {
+      if (campaign.archived) stats.archived_campaigns++;
+      else stats.active_campaigns++;
+
+      for (const run of campaign.runs) {
+        stats.total_runs++;
+        if (run.archived) stats.archived_runs++;
+        else stats.active_runs++;
+
+        for (const compound of run.compounds) {
+          stats.total_compounds++;
+          byStatus[compound.status]++;
+          if (isTerminal(compound.status)) terminalCount++;
+        }
+      }
+    }
+
+    stats.total_completed = byStatus.COMPLETED;
+    stats.success_rate = terminalCount > 0 ? stats.total_completed / terminalCount : 0;
+
+    return stats;
+  }),
+
+  getSize: publicProcedure.query(({ ctx }) => {
+    return computeWorkspaceSize(ctx.services.state.rootDir);
+  }),
+
+  getPollerStatus: publicProcedure.query(({ ctx }) => {
+    return ctx.services.poller.getStatus();
+  }),
+
+  listBackups: publicProcedure.query(({ ctx }) => {
+    return listBackups(ctx.services.state.rootDir);
+  }),
+
+  restoreBackup: publicProcedure
+    .input(z.object({ backupName: z.string().min(1) }))
+    .mutation(({ ctx, input }) => {
+      const { state, eventBus } = ctx.services;
+
+      // Active submissions/downloads would be orphaned by swapping the tree
+      const active = state.data.campaigns.some((c) =>
+        c.runs.some((r) => r.compounds.some((co) => !isTerminal(co.status))),
+      );
+      if (active) {
+        throw new Error('Cannot restore a backup while compounds are still in progress');
+      }
+
+      const data = readBackup(state.rootDir, input.backupName);
+
+      state.data = data;
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+
+      eventBus.emit('state-restored', {
+        backup_name: input.backupName,
+      } satisfies StateRestoredEvent);
+    }),
+
+  /** Reload state.json from disk, discarding unsaved in-memory changes */
+  reloadFromDisk: publicProcedure.mutation(async ({ ctx }) => {
+    await ctx.services.reloadStateFromDisk();
+  }),
+
+  validate: publicProcedure.query(({ ctx }) => {
+    const { state } = ctx.services;
+    return validateWorkspace(state.rootDir, state.data);
+  }),
+
+  /** Campaign and run folders on disk with no matching state entry */
+  listOrphanedFolders: publicProcedure.query(({ ctx }) => {
+    const { state } = ctx.services;
+    return scanOrphanedFolders(state.rootDir, state.data);
+  }),
+
+  deleteOrphanedFolder: publicProcedure
+    .input(z.object({ path: z.string().min(1) }))
+    .mutation(async ({ ctx, input }) => {
+      const { state } = ctx.services;
+      await deleteOrphanedFolder(state.rootDir, state.data, input.path);
+    }),
+
+  compactArchive: publicProcedure
+    .input(z.object({ olderThanDays: z.number().int().min(0) }))
+    .mutation(({ ctx, input }) => {
+      const { state } = ctx.services;
+      const summary = state.gcArchivedData(input.olderThanDays * 86_400_000);
+      if (summary.runs_compacted > 0) {
+        persistState(state.rootDir, state.data);
+      }
+      return summary;
+    }),
+
+  checkIntegrity: publicProcedure.query(({ ctx }) => {
+    return checkIntegrity(ctx.services.state.data);
+  }),
+
+  repair: publicProcedure.mutation(({ ctx }) => {
+    const { state } = ctx.services;
+    const report = validateWorkspace(state.rootDir, state.data);
+    const result: RepairReport = {
+      folders_created: 0,
+      compounds_queued_for_redownload: 0,
+      errors: [],
+    };
+
+    // Campaign folders first so run folders have a parent
+    const missingFolders = [...report.missing_campaign_folders, ...report.missing_run_folders];
+    for (const missing of missingFolders) {
+      try {
+        fs.mkdirSync(missing.path, { recursive: true });
+        result.folders_created++;
+      } catch (e) {
+        const msg = e instanceof Error ? e.message : String(e);
+        result.errors.push(`Failed to create ${missing.path}: ${msg}`);
+      }
+    }
+
+    // Flag compounds with missing outputs as DOWNLOAD_FAILED so the
+    // poller's periodic download retry picks them up. Compounds already
+    // flagged are queued as it is and aren't counted again.
+    for (const ref of scanIncompleteDownloads(state.rootDir, state.data)) {
+      const compound = state.findCompound(ref.compound_id);
+      if (compound && compound.status !== 'DOWNLOAD_FAILED') {
+        compound.status = 'DOWNLOAD_FAILED';
+        compound.download_error ??= 'Missing output files';
+        result.compounds_queued_for_redownload++;
+      }
+    }
+
+    if (result.compounds_queued_for_redownload > 0) {
+      state.markDirty();
+      persistState(state.rootDir, state.data);
+    }
+
+    return result;
+  }),
+
+  listDownloadErrors: publicProcedure.query(({ ctx }) => {
+    return ctx.services.state.scanDownloadErrors().map(
+      ([campaign, run, compound]): CompoundWithContext => ({
+        ...compound,
+        campaign_id: campaign.id,
+        campaign_name: campaign.display_name,
+        run_id: run.id,
+        run_name: run.display_name,
+      }),
+    );
+  }),
+
+  /**
+   * Every COMPLETED compound across all campaigns as one CSV, one row per
+   * compound with sample_<i>_<metric> columns for each sample. Chain-level
+   * maps are JSON-encoded. Fields are quoted per RFC 4180.
+   */
+  exportMetricsCsv: publicProcedure.query(({ ctx }) => {
+    const completed = ctx.services.state
+      .allCompoundContexts()
+      .filter(([, , compound]) => compound.status === 'COMPLETED');
+    const sampleCount = completed.reduce(
+      (max, [, , compound]) => Math.max(max, compound.metrics?.samples.length ?? 0),
+      0,
+    );
+
+    const fields = [
+      'campaign_name',
+      'campaign_id',
+      'run_name',
+      'run_id',
+      'compound_name',
+      'compound_id',
+      'smiles',
+      'status',
+      'submitted_at',
+      'completed_at',
+      'binding_confidence',
+      'optimization_score',
+    ];
+    for (let i = 0; i < sampleCount; i++) {
+      for (const metric of SAMPLE_METRIC_FIELDS) fields.push(`sample_${i}_${metric}`);
+    }
+
+    const rows = completed.map(([campaign, run, compound]) => {
+      const row: unknown[] = [
+        campaign.display_name,
+        campaign.id,
+        run.display_name,
+        run.id,
+        compound.display_name,
+        compound.id,
+        compound.smiles,
+        compound.status,
+        compound.submitted_at ?? '',
+        compound.completed_at ?? '',
+        compound.metrics?.affinity?.binding_confidence ?? '',
+        compound.metrics?.affinity?.optimization_score ?? '',
+      ];
+      for (let i = 0; i < sampleCount; i++) {
+        const sample = compound.metrics?.samples[i];
+        for (const metric of SAMPLE_METRIC_FIELDS) {
+          const value = sample?.[metric] ?? '';
+          row.push(typeof value === 'object' ? JSON.stringify(value) : value);
+        }
+      }
+      return row;
+    });
+
+    return Papa.unparse({ fields, data: rows }, { quotes: false, newline: '\r\n' });
+  }),
+
+  /** Completed compounds whose outputs are still missing, for the Downloads panel */
+  getDownloadQueue: publicProcedure.query(({ ctx }) => {
+    const { state } = ctx.services;
+    const status: DownloadQueueStatus = { pending_downloads: [], failed_downloads: [] };
+
+    for (const [campaign, run, compound] of state.allCompoundContexts()) {
+      if (compound.status !== 'COMPLETED' && compound.status !== 'DOWNLOAD_FAILED') continue;
+      const entry: DownloadQueueEntry = {
+        compound_id: compound.id,
+        display_name: compound.display_name,
+        campaign_name: campaign.display_name,
+        run_name: run.display_name,
+        boltz_job_id: compound.boltz_job_id,
+        download_error: compound.download_error,
+      };
+
+      if (compound.download_error || compound.status === 'DOWNLOAD_FAILED') {
+        status.failed_downloads.push(entry);
+        continue;
+      }
+      const cifPath = path.join(
+        state.rootDir,
+        campaign.folder_name,
+        run.folder_name,
+        compound.folder_name,
+        'sample_0_structure.cif',
+      );
+      if (!fs.existsSync(cifPath)) status.pending_downloads.push(entry);
+    }
+    return status;
+  }),
+
+  /** FAILED and TIMED_OUT compounds, newest first, for deciding what to retry */
+  getFailedCompoundsSummary: publicProcedure
+    .input(z.object({ campaignId: z.string().uuid().optional() }).default({}))
+    .query(({ ctx, input }) => {
+      const { state } = ctx.services;
+      if (input.campaignId && !state.findCampaign(input.campaignId)) {
+        throw new Error('Campaign not found');
+      }
+
+      const entries: FailedCompoundEntry[] = [];
+      for (const [campaign, run, compound] of state.allCompoundContexts()) {
+        if (input.campaignId && campaign.id !== input.campaignId) continue;
+        if (compound.status !== 'FAILED' && compound.status !== 'TIMED_OUT') continue;
+        entries.push({
+          compound_id: compound.id,
+          display_name: compound.display_name,
+          smiles: compound.smiles,
+          campaign_name: campaign.display_name,
+          run_name: run.display_name,
+          status: compound.status,
+          error_message: compound.error_message,
+          completed_at: compound.completed_at,
+        });
+      }
+      // ISO timestamps sort lexicographically; entries without one go last
+      entries.sort((a, b) => (b.completed_at ?? '').localeCompare(a.completed_at ?? ''));
+      return entries;
+    }),
+
+  /** Distribution of one metric over completed compounds in the workspace, a campaign, or a run */
+  getMetricsHistogram: publicProcedure
+    .input(
+      z.object({
+        scope: z.discriminatedUnion('type', [
+          z.object({ type: z.literal('all') }),
+          z.object({ type: z.literal('campaign'), campaignId: z.string().uuid() }),
+          z.object({ type: z.literal('run'), runId: z.string().uuid() }),
+        ]),
+        metric: metricFieldSchema,
+        bucketCount: z.number().int().min(1).max(100).default(10),
+      }),
+    )
+    .query(({ ctx, input }) => {
+      const { state } = ctx.services;
+      let contexts = state.allCompoundContexts();
+      const { scope } = input;
+      if (scope.type === 'campaign') {
+        if (!state.findCampaign(scope.campaignId)) throw new Error('Campaign not found');
+        contexts = contexts.filter(([campaign]) => campaign.id === scope.campaignId);
+      } else if (scope.type === 'run') {
+        if (!state.findRun(scope.runId)) throw new Error('Run not found');
+        contexts = contexts.filter(([, run]) => run.id === scope.runId);
+      }
+
+      const values: number[] = [];
+      for (const [, , compound] of contexts) {
+        if (compound.status !== 'COMPLETED') continue;
+        const v = metricValue(compound, input.metric);
+        if (v !== null) values.push(v);
+      }
+      return buildHistogram(values, input.bucketCount);
+    }),
+
+  getAuditLog: publicProcedure
+    .input(
+      z.object({
+        entityId: z.string().uuid().optional(),
+        limit: z.number().int().min(1),
+      }),
+    )
+    .query(({ ctx, input }): AuditEntry[] => {
+      // Newest first
+      const entries: AuditEntry[] = [];
+      const log = ctx.services.state.data.audit_log;
+      for (let i = log.length - 1; i >= 0 && entries.length < input.limit; i--) {
+        if (!input.entityId || log[i].entity_id === input.entityId) entries.push(log[i]);
+      }
+      return entries;
+    }),
+
+  /** Run and compound milestones across all campaigns, newest first */
+  getRecentActivity: publicProcedure
+    .input(z.object({ limit: z.number().int().min(1).max(500).default(50) }))
+    .query(({ ctx, input }) => {
+      const events: ActivityEvent[] = [];
+      for (const campaign of ctx.services.state.data.campaigns) {
+        const base = { campaign_id: campaign.id, campaign_name: campaign.display_name };
+        for (const run of campaign.runs) {
+          const runEvent = { ...base, entity_id: run.id, entity_name: run.display_name, run_id: null, run_name: null };
+          events.push({ ...runEvent, event_type: 'run_created', timestamp: run.created_at });
+          if (run.completed_at) {
+            events.push({ ...runEvent, event_type: 'run_completed', timestamp: run.completed_at });
+          }
+
+          for (const c of run.compounds) {
+            const compoundEvent = {
+              ...base,
+              entity_id: c.id,
+              entity_name: c.display_name,
+              run_id: run.id,
+              run_name: run.display_name,
+            };
+            if (c.submitted_at) {
+              events.push({ ...compoundEvent, event_type: 'compound_submitted', timestamp: c.submitted_at });
+            }
+            if (c.status === 'COMPLETED' && c.completed_at) {
+              events.push({ ...compoundEvent, event_type: 'compound_completed', timestamp: c.completed_at });
+            }
+          }
+        }
+      }
+
+      events.sort((a, b) => b.timestamp.localeCompare(a.timestamp));
+      return events.slice(0, input.limit);
+    }),
+
+  listRemotePredictions: publicProcedure
+    .input(z.object({ page: z.number().int().min(0) }))
+    .query(({ ctx, input }) => {
+      const { state, client } = ctx.services;
+      const apiKey = state.activeApiKey();
+      if (!apiKey) throw new Error('No API key configured');
+
+      return client.listPredictionsPaged(apiKey, input.page, REMOTE_PREDICTIONS_PAGE_SIZE);
+    }),
+
+  // ── Subscriptions ──────────────────────────────────────────────────
+
+  onStateRestored: publicProcedure.subscription(({ ctx }) => {
+    return observable<StateRestoredEvent>((emit) => {
+      const handler = (event: StateRestoredEvent) => emit.next(event);
+      ctx.services.eventBus.on('state-restored', handler);
+      return () => {
+        ctx.services.eventBus.off('state-restored', handler);
+      };
+    });
+  }),
+
+  onStateReloaded: publicProcedure.subscription(({ ctx }) => {
+    return observable<StateReloadedEvent>((emit) => {
+      const handler = (event: StateReloadedEvent) => emit.next(event);
+      ctx.services.eventBus.on('state-reloaded', handler);
+      return () => {
+        ctx.services.eventBus.off('state-reloaded', handler);
+      };
+    });
+  }),
+});
@@ -0,0 +1,43 @@
+import { z } from 'zod';
+import { ALL_JOB_STATUSES, NUMERIC_SAMPLE_METRICS } from '../models/types';
+
+// ── Shared Input Schemas ────────────────────────────────────────────
+
+export const runParamsSchema = z.object({
+  recycling_steps: z.number().int().min(1),
+  diffusion_samples: z.number().int().min(1),
+  sampling_steps: z.number().int().min(1),
+  step_scale: z.number().positive(),
+  timeout_secs: z.number().int().positive().nullable().default(null),
+  mode: z.enum(['protein_ligand', 'protein_only']).default('protein_ligand'),
+});
+
+// smiles may be empty for protein_only runs; validateSmiles checks the rest
+export const compoundInputSchema = z.object({
+  name: z.string().min(1),
+  smiles: z.string(),
+});
+
+export const multiLigandCompoundSchema = z.object({
+  name: z.string().min(1),
+  ligands: z
+    .array(
+      z.object({
+        smiles: z.string().min(1),
+        chain_id: z.string().regex(/^[B-Z]$/, 'Ligand chain IDs must be a single letter B–Z'),
+      }),
+    )
+    .min(1)
+    .refine(
+      (ligands) => new Set(ligands.map((l) => l.chain_id)).size === ligands.length,
+      'Ligand chain IDs must be unique',
+    ),
+});
+
+export const jobStatusSchema = z.enum(ALL_JOB_STATUSES);
+
+export const metricFieldSchema = z.enum([
+  'binding_confidence',
+  'optimization_score',
+  ...NUMERIC_SAMPLE_METRICS,
+]);
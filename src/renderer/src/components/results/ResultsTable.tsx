@@ -373,6 +373,7 @@ function StatusBadge({ status }: { status: string }) {
     FAILED: 'text-red-600 dark:text-red-400',
     TIMED_OUT: 'text-amber-600 dark:text-amber-400',
     CANCELLED: 'text-muted-foreground',
+    DOWNLOAD_FAILED: 'text-amber-600 dark:text-amber-400',
   };
 
   return (
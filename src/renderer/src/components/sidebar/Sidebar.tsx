@@ -52,7 +52,11 @@ function RunProgressSummary({ run }: { run: Run }) {
     (c) => c.status === 'RUNNING' || c.status === 'CREATED',
   ).length;
   const failed = run.compounds.filter(
-    (c) => c.status === 'FAILED' || c.status === 'TIMED_OUT' || c.status === 'CANCELLED',
+    (c) =>
+      c.status === 'FAILED' ||
+      c.status === 'TIMED_OUT' ||
+      c.status === 'CANCELLED' ||
+      c.status === 'DOWNLOAD_FAILED',
   ).length;
   const total = run.compounds.length;
 
@@ -45,6 +45,27 @@ export function useBackendEvents() {
     },
   });
 
+  // State restored from backup → everything may have changed
+  trpc.workspace.onStateRestored.useSubscription(undefined, {
+    onData: () => {
+      queryClient.invalidateQueries();
+    },
+  });
+
+  // state.json reloaded from disk → the whole tree was replaced
+  trpc.workspace.onStateReloaded.useSubscription(undefined, {
+    onData: () => {
+      queryClient.invalidateQueries();
+    },
+  });
+
+  // Workspace switched → the whole tree was replaced
+  trpc.settings.onWorkspaceSwitched.useSubscription(undefined, {
+    onData: () => {
+      queryClient.invalidateQueries();
+    },
+  });
+
   // Request notification permission on mount
   useEffect(() => {
     if (Notification.permission === 'default') {
@@ -0,0 +1,81 @@
+import { test, expect } from '@playwright/test';
+import { BoltzApiError, BoltzClient } from '../../src/main/services/boltz-client';
+import { RETRY_ATTEMPTS_RATE_LIMIT } from '../../src/main/models/types';
+
+const realFetch = globalThis.fetch;
+
+/** Replace global fetch with one that answers from a queue (the last response repeats) */
+function mockFetch(responses: (() => Response)[]): { calls: number } {
+  const state = { calls: 0 };
+  globalThis.fetch = async () => {
+    const next = responses[Math.min(state.calls, responses.length - 1)];
+    state.calls++;
+    return next();
+  };
+  return state;
+}
+
+const rateLimited = (retryAfter: string) => () =>
+  new Response('slow down', { status: 429, headers: { 'Retry-After': retryAfter } });
+
+const submitted = () =>
+  new Response(JSON.stringify({ prediction_id: 'pred-1' }), {
+    status: 200,
+    headers: { 'Content-Type': 'application/json' },
+  });
+
+function submit(client: BoltzClient, onRateLimited?: (delayMs: number) => void) {
+  return client.submitPrediction('sk-test', {}, {}, 'test', { onRateLimited });
+}
+
+test.describe('BoltzClient rate limiting', () => {
+  test.afterEach(() => {
+    globalThis.fetch = realFetch;
+  });
+
+  test('waits for Retry-After: 2 before retrying a 429', async () => {
+    const fetchMock = mockFetch([rateLimited('2'), submitted]);
+    const delays: number[] = [];
+
+    const started = Date.now();
+    const result = await submit(new BoltzClient(), (ms) => delays.push(ms));
+
+    expect(result).toEqual({ prediction_id: 'pred-1' });
+    expect(fetchMock.calls).toBe(2);
+    expect(delays).toEqual([2000]);
+    expect(Date.now() - started).toBeGreaterThanOrEqual(2000);
+  });
+
+  test('accepts Retry-After as an HTTP date', async () => {
+    const at = new Date(Date.now() + 2000).toUTCString(); // whole seconds, so 1–2s away
+    mockFetch([rateLimited(at), submitted]);
+    const delays: number[] = [];
+
+    await submit(new BoltzClient(), (ms) => delays.push(ms));
+
+    expect(delays).toHaveLength(1);
+    expect(delays[0]).toBeGreaterThan(500);
+    expect(delays[0]).toBeLessThanOrEqual(2000);
+  });
+
+  test(`gives up after ${RETRY_ATTEMPTS_RATE_LIMIT} rate-limited attempts`, async () => {
+    const fetchMock = mockFetch([rateLimited('0')]);
+
+    const err = await submit(new BoltzClient()).catch((e: unknown) => e);
+
+    expect(err).toBeInstanceOf(BoltzApiError);
+    expect((err as BoltzApiError).statusCode).toBe(429);
+    expect(fetchMock.calls).toBe(RETRY_ATTEMPTS_RATE_LIMIT);
+  });
+
+  test('does not retry a permanent error', async () => {
+    const fetchMock = mockFetch([() => new Response('bad input', { status: 422 })]);
+    const delays: number[] = [];
+
+    await expect(submit(new BoltzClient(), (ms) => delays.push(ms))).rejects.toThrow(
+      /Submit failed \(422\)/,
+    );
+    expect(fetchMock.calls).toBe(1);
+    expect(delays).toEqual([]);
+  });
+});
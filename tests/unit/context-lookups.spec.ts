@@ -0,0 +1,98 @@
+import { test, expect } from '@playwright/test';
+import { makeCampaign, makeCompound, makeRun, makeState, testId } from './helpers/fixtures';
+
+function fixture() {
+  return makeState([
+    makeCampaign('Kinase', [
+      makeRun('Screen', [makeCompound('a', 'CCO'), makeCompound('b', 'CCN')]),
+      makeRun('Follow-up', [makeCompound('c', 'CCC')]),
+    ]),
+    makeCampaign('Protease', [makeRun('Run 1', [makeCompound('d', 'CCCl')])]),
+  ]);
+}
+
+test.describe('findCompoundContext', () => {
+  test('resolves every compound to its own campaign and run inside a loop', () => {
+    const state = fixture();
+    for (const campaign of state.data.campaigns) {
+      for (const run of campaign.runs) {
+        for (const compound of run.compounds) {
+          const context = state.findCompoundContext(compound.id);
+          expect(context).toBeDefined();
+          const [foundCampaign, foundRun, foundCompound] = context!;
+          expect(foundCampaign).toBe(campaign);
+          expect(foundRun).toBe(run);
+          expect(foundCompound).toBe(compound);
+        }
+      }
+    }
+  });
+
+  test('mutations through the context land in state while iterating', () => {
+    const state = fixture();
+    for (const [, , compound] of state.allCompoundContexts()) {
+      const [campaign, run, found] = state.findCompoundContext(compound.id)!;
+      found.status = 'CANCELLED';
+      found.note = `${campaign.display_name}/${run.display_name}`;
+    }
+
+    const compounds = state.data.campaigns.flatMap((c) => c.runs.flatMap((r) => r.compounds));
+    expect(compounds.map((c) => c.status)).toEqual(['CANCELLED', 'CANCELLED', 'CANCELLED', 'CANCELLED']);
+    expect(compounds.map((c) => c.note)).toEqual([
+      'Kinase/Screen',
+      'Kinase/Screen',
+      'Kinase/Follow-up',
+      'Protease/Run 1',
+    ]);
+  });
+
+  test('a run mutated through one lookup is seen by the next', () => {
+    const state = fixture();
+    const [, run] = state.findCompoundContext(state.data.campaigns[0].runs[0].compounds[0].id)!;
+    for (const compound of run.compounds) {
+      const [, sameRun] = state.findCompoundContext(compound.id)!;
+      sameRun.paused = !sameRun.paused;
+    }
+    // Toggled once per compound in the run (two), so back to unpaused
+    expect(state.data.campaigns[0].runs[0].paused).toBe(false);
+  });
+
+  test('returns undefined for an unknown compound', () => {
+    expect(fixture().findCompoundContext(testId())).toBeUndefined();
+  });
+});
+
+test.describe('findRunContext', () => {
+  test('resolves every run to its own campaign inside a loop', () => {
+    const state = fixture();
+    for (const campaign of state.data.campaigns) {
+      for (const run of campaign.runs) {
+        const [foundCampaign, foundRun] = state.findRunContext(run.id)!;
+        expect(foundCampaign).toBe(campaign);
+        expect(foundRun).toBe(run);
+      }
+    }
+  });
+
+  test('mutations through the context land in state while iterating', () => {
+    const state = fixture();
+    for (const campaign of state.data.campaigns) {
+      for (const run of campaign.runs) {
+        const [foundCampaign, foundRun] = state.findRunContext(run.id)!;
+        foundRun.note = foundCampaign.display_name;
+        for (const compound of foundRun.compounds) compound.archived = true;
+      }
+    }
+
+    for (const campaign of state.data.campaigns) {
+      for (const run of campaign.runs) {
+        expect(run.note).toBe(campaign.display_name);
+        expect(run.compounds.every((c) => c.archived)).toBe(true);
+      }
+    }
+  });
+
+  test('returns undefined for an unknown run', () => {
+    expect(fixture().findRunContext(testId())).toBeUndefined();
+  });
+});
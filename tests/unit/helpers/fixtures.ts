@@ -0,0 +1,77 @@
+import { AppState } from '../../../src/main/models/state';
+import { DEFAULT_RUN_PARAMS } from '../../../src/main/models/types';
+import type { Campaign, Compound, Run } from '../../../src/main/models/types';
+
+let nextId = 0;
+
+/** Deterministic UUID-shaped ids, unique within a test process */
+export function testId(): string {
+  nextId++;
+  return `00000000-0000-4000-8000-${nextId.toString().padStart(12, '0')}`;
+}
+
+export function makeCompound(name: string, smiles: string, overrides: Partial<Compound> = {}): Compound {
+  return {
+    id: testId(),
+    display_name: name,
+    folder_name: name,
+    smiles,
+    smiles_list: [{ smiles, chain_id: 'B' }],
+    boltz_job_id: null,
+    status: 'PENDING',
+    submitted_at: null,
+    completed_at: null,
+    metrics: null,
+    error_message: null,
+    download_error: null,
+    download_attempts: 0,
+    last_download_attempt_at: null,
+    download_checksum: null,
+    cif_checksum: null,
+    input_hash: null,
+    note: null,
+    archived: false,
+    archived_at: null,
+    ...overrides,
+  };
+}
+
+export function makeRun(name: string, compounds: Compound[]): Run {
+  return {
+    id: testId(),
+    display_name: name,
+    folder_name: name,
+    archived: false,
+    archived_at: null,
+    params: { ...DEFAULT_RUN_PARAMS },
+    note: null,
+    paused: false,
+    created_at: '2025-01-01T00:00:00.000Z',
+    completed_at: null,
+    display_order: 0,
+    compounds,
+    compacted: null,
+  };
+}
+
+export function makeCampaign(name: string, runs: Run[]): Campaign {
+  return {
+    id: testId(),
+    display_name: name,
+    folder_name: name,
+    target_sequence: 'MKTAYIAK',
+    target_type: 'protein',
+    description: null,
+    note: null,
+    archived: false,
+    archived_at: null,
+    created_at: '2025-01-01T00:00:00.000Z',
+    display_order: 0,
+    runs,
+  };
+}
+
+/** In-memory AppState over the given campaigns; nothing touches disk */
+export function makeState(campaigns: Campaign[]): AppState {
+  return new AppState({ ...AppState.defaultData(), campaigns }, '/nonexistent');
+}
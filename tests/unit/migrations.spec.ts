@@ -0,0 +1,251 @@
+import { test, expect } from '@playwright/test';
+import { migrateAppData } from '../../src/main/services/storage';
+import { CURRENT_SCHEMA_VERSION } from '../../src/main/models/types';
+import type { AppData } from '../../src/main/models/types';
+
+type Json = Record<string, unknown>;
+
+/**
+ * A state.json as written by the given schema version: one campaign with
+ * one run and two compounds, holding only the fields that version knew.
+ */
+function fixtureAt(version: number): AppData {
+  const compound = (id: string, name: string, smiles: string): Json => ({
+    id,
+    display_name: name,
+    folder_name: name,
+    smiles,
+    boltz_job_id: `job-${id}`,
+    status: 'COMPLETED',
+    submitted_at: '2025-01-01T00:00:00.000Z',
+    completed_at: '2025-01-01T01:00:00.000Z',
+    metrics: {
+      affinity: { binding_confidence: 0.8, optimization_score: 0.5 },
+      samples: [],
+    },
+    error_message: null,
+  });
+  const compounds = [
+    compound('c1', 'aspirin', 'CC(=O)OC1=CC=CC=C1C(=O)O'),
+    compound('c2', 'ethanol', 'CCO'),
+  ];
+  const run: Json = {
+    id: 'r1',
+    display_name: 'Run 1',
+    folder_name: 'Run_1',
+    archived: false,
+    archived_at: null,
+    params: { recycling_steps: 3, diffusion_samples: 1, sampling_steps: 200, step_scale: 1.5 },
+    created_at: '2025-01-01T00:00:00.000Z',
+    completed_at: null,
+    compounds,
+  };
+  const campaign: Json = {
+    id: 'k1',
+    display_name: 'Kinase',
+    folder_name: 'Kinase',
+    description: null,
+    archived: false,
+    archived_at: null,
+    created_at: '2025-01-01T00:00:00.000Z',
+    runs: [run],
+  };
+  const data: Json = { schema_version: version, campaigns: [campaign] };
+
+  if (version >= 2) for (const c of compounds) c.download_error = null;
+  if (version >= 3) {
+    campaign.target_type = 'protein';
+    campaign.target_sequence = 'MKTAYIAK';
+  } else {
+    campaign.protein_sequence = 'MKTAYIAK';
+  }
+  if (version >= 4) {
+    campaign.note = null;
+    run.note = null;
+    for (const c of compounds) c.note = null;
+  }
+  if (version >= 5) {
+    for (const c of compounds) {
+      c.download_checksum = null;
+      c.cif_checksum = null;
+    }
+  }
+  if (version >= 7) run.paused = false;
+  if (version >= 8) for (const c of compounds) c.input_hash = `hash-${c.id}`;
+  if (version >= 9) (run.params as Json).timeout_secs = null;
+  if (version >= 10) data.audit_log = [];
+  if (version >= 11) {
+    for (const c of compounds) {
+      c.archived = false;
+      c.archived_at = null;
+    }
+  }
+  if (version >= 12) {
+    data.api_keys = [{ id: 'key-1', label: 'Default', key: 'sk-test', is_default: true }];
+    data.active_api_key_id = 'key-1';
+  } else {
+    data.api_key = 'sk-test';
+  }
+  if (version >= 13) run.compacted = null;
+  if (version >= 14) {
+    for (const c of compounds) c.smiles_list = [{ smiles: c.smiles, chain_id: 'B' }];
+  }
+  if (version >= 15) (run.params as Json).mode = 'protein_ligand';
+  if (version >= 16) data.templates = [];
+  if (version >= 17) {
+    campaign.display_order = 0;
+    run.display_order = 0;
+  }
+  if (version >= 18) {
+    for (const c of compounds) {
+      c.download_attempts = 0;
+      c.last_download_attempt_at = null;
+    }
+  }
+
+  return data as unknown as AppData;
+}
+
+/** Every field the current schema requires, with its type */
+function expectCurrentShape(data: AppData): void {
+  expect(data.schema_version).toBe(CURRENT_SCHEMA_VERSION);
+  expect(Array.isArray(data.api_keys)).toBe(true);
+  expect(data).toHaveProperty('active_api_key_id');
+  expect(data).not.toHaveProperty('api_key');
+  expect(data.templates).toEqual([]);
+  expect(data.audit_log).toEqual([]);
+
+  const campaign = data.campaigns[0];
+  expect(campaign.target_type).toBe('protein');
+  expect(campaign.target_sequence).toBe('MKTAYIAK');
+  expect(campaign).not.toHaveProperty('protein_sequence');
+  expect(campaign.note).toBeNull();
+  expect(campaign.display_order).toBe(0);
+
+  const run = campaign.runs[0];
+  expect(run.note).toBeNull();
+  expect(run.paused).toBe(false);
+  expect(run.compacted).toBeNull();
+  expect(run.display_order).toBe(0);
+  expect(run.params.timeout_secs).toBeNull();
+  expect(run.params.mode).toBe('protein_ligand');
+
+  for (const compound of run.compounds) {
+    expect(compound.download_error).toBeNull();
+    expect(compound.download_checksum).toBeNull();
+    expect(compound.cif_checksum).toBeNull();
+    expect(compound.note).toBeNull();
+    expect(compound.input_hash).toEqual(expect.any(String));
+    expect(compound.archived).toBe(false);
+    expect(compound.archived_at).toBeNull();
+    expect(compound.smiles_list).toEqual([{ smiles: compound.smiles, chain_id: 'B' }]);
+    expect(compound.download_attempts).toBe(0);
+    expect(compound.last_download_attempt_at).toBeNull();
+  }
+}
+
+test.describe('migrateAppData', () => {
+  for (let version = 1; version <= CURRENT_SCHEMA_VERSION; version++) {
+    test(`upgrades a v${version} state to the current shape`, () => {
+      expectCurrentShape(migrateAppData(fixtureAt(version)));
+    });
+  }
+
+  test('treats a missing schema_version as v1', () => {
+    const data = fixtureAt(1) as unknown as Json;
+    delete data.schema_version;
+    expectCurrentShape(migrateAppData(data as unknown as AppData));
+  });
+
+  test('leaves a current state untouched', () => {
+    const data = fixtureAt(CURRENT_SCHEMA_VERSION);
+    const before = structuredClone(data);
+    expect(migrateAppData(data)).toEqual(before);
+  });
+
+  test('rejects a state written by a newer version', () => {
+    expect(() => migrateAppData(fixtureAt(CURRENT_SCHEMA_VERSION + 1))).toThrow(
+      /Unsupported state schema version/,
+    );
+  });
+
+  test('v1: lifts api_key into api_keys as the active Default entry', () => {
+    const data = migrateAppData(fixtureAt(1));
+    expect(data.api_keys).toEqual([
+      { id: expect.any(String), label: 'Default', key: 'sk-test', is_default: true },
+    ]);
+    expect(data.active_api_key_id).toBe(data.api_keys[0].id);
+  });
+
+  test('v11: a state without an api_key gets no entries', () => {
+    const legacy = fixtureAt(11) as unknown as Json;
+    legacy.api_key = null;
+    const data = migrateAppData(legacy as unknown as AppData);
+    expect(data.api_keys).toEqual([]);
+    expect(data.active_api_key_id).toBeNull();
+  });
+
+  test('v12: existing api_keys are kept as-is', () => {
+    const data = migrateAppData(fixtureAt(12));
+    expect(data.api_keys).toEqual([
+      { id: 'key-1', label: 'Default', key: 'sk-test', is_default: true },
+    ]);
+    expect(data.active_api_key_id).toBe('key-1');
+  });
+
+  test('v1: nulls placeholder zero affinity and keeps real values', () => {
+    const legacy = fixtureAt(1);
+    legacy.campaigns[0].runs[0].compounds[0].metrics!.affinity = {
+      binding_confidence: 0,
+      optimization_score: 0,
+    };
+    const [placeholder, real] = migrateAppData(legacy).campaigns[0].runs[0].compounds;
+    expect(placeholder.metrics!.affinity).toBeNull();
+    expect(real.metrics!.affinity).toEqual({ binding_confidence: 0.8, optimization_score: 0.5 });
+  });
+
+  for (const version of [2, 5]) {
+    test(`v${version}: COMPLETED with a download_error becomes DOWNLOAD_FAILED`, () => {
+      const legacy = fixtureAt(version);
+      legacy.campaigns[0].runs[0].compounds[0].download_error = 'Download failed (500)';
+      const [failed, completed] = migrateAppData(legacy).campaigns[0].runs[0].compounds;
+      expect(failed.status).toBe('DOWNLOAD_FAILED');
+      expect(failed.download_error).toBe('Download failed (500)');
+      expect(completed.status).toBe('COMPLETED');
+    });
+  }
+
+  test('v7: input_hash is computed, and matches for identical inputs', () => {
+    const legacy = fixtureAt(7);
+    legacy.campaigns[0].runs[0].compounds[1].smiles = 'CC(=O)OC1=CC=CC=C1C(=O)O';
+    const [a, b] = migrateAppData(legacy).campaigns[0].runs[0].compounds;
+    expect(a.input_hash).toMatch(/^[0-9a-f]{64}$/);
+    expect(b.input_hash).toBe(a.input_hash);
+  });
+
+  test('v8+: an existing input_hash is kept', () => {
+    const [compound] = migrateAppData(fixtureAt(8)).campaigns[0].runs[0].compounds;
+    expect(compound.input_hash).toBe('hash-c1');
+  });
+
+  test('v16: display_order follows the stored order', () => {
+    const legacy = fixtureAt(16);
+    const second = structuredClone(legacy.campaigns[0]);
+    second.id = 'k2';
+    legacy.campaigns.push(second);
+    legacy.campaigns[0].runs.push(structuredClone(legacy.campaigns[0].runs[0]));
+
+    const data = migrateAppData(legacy);
+    expect(data.campaigns.map((c) => c.display_order)).toEqual([0, 1]);
+    expect(data.campaigns[0].runs.map((r) => r.display_order)).toEqual([0, 1]);
+  });
+
+  test('keeps values set before the field was migrated', () => {
+    const legacy = fixtureAt(4);
+    legacy.campaigns[0].note = 'hinge binders';
+    legacy.campaigns[0].runs[0].compounds[0].note = 'lead';
+    const data = migrateAppData(legacy);
+    expect(data.campaigns[0].note).toBe('hinge binders');
+    expect(data.campaigns[0].runs[0].compounds[0].note).toBe('lead');
+  });
+});
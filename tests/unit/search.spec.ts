@@ -0,0 +1,89 @@
+import { test, expect } from '@playwright/test';
+import { makeCampaign, makeCompound, makeRun, makeState } from './helpers/fixtures';
+
+function fixture() {
+  const aspirin = makeCompound('Aspirin', 'CC(=O)OC1=CC=CC=C1C(=O)O', {
+    submitted_at: '2025-01-02T00:00:00.000Z',
+  });
+  const ethanol = makeCompound('ethanol', 'CCO', { submitted_at: '2025-01-03T00:00:00.000Z' });
+  const draft = makeCompound('Aspirin analogue', 'CC(=O)Oc1ccccc1', { submitted_at: null });
+  const caffeine = makeCompound('Caffeine', 'CN1C=NC2=C1C(=O)N(C(=O)N2C)C', {
+    submitted_at: '2025-01-01T00:00:00.000Z',
+  });
+
+  const screen = makeRun('Screen', [aspirin, ethanol]);
+  const followUp = makeRun('Follow-up', [draft]);
+  const kinase = makeCampaign('Kinase', [screen, followUp]);
+  const other = makeCampaign('Other', [makeRun('Run 1', [caffeine])]);
+
+  return {
+    state: makeState([kinase, other]),
+    kinase,
+    screen,
+    compounds: { aspirin, ethanol, draft, caffeine },
+  };
+}
+
+test.describe('searchCompounds', () => {
+  test('matches display names case-insensitively', () => {
+    const { state, compounds } = fixture();
+    const ids = state.searchCompounds('ASPIRIN').map((r) => r.id);
+    expect(ids).toEqual([compounds.aspirin.id, compounds.draft.id]);
+  });
+
+  test('matches SMILES substrings case-insensitively', () => {
+    const { state, compounds } = fixture();
+    expect(state.searchCompounds('cco').map((r) => r.id)).toEqual([compounds.ethanol.id]);
+    expect(state.searchCompounds('oc1ccccc1').map((r) => r.id)).toEqual([compounds.draft.id]);
+  });
+
+  test('trims the query', () => {
+    const { state, compounds } = fixture();
+    expect(state.searchCompounds('  caffeine  ').map((r) => r.id)).toEqual([compounds.caffeine.id]);
+  });
+
+  test('an empty query matches everything, most recently submitted first', () => {
+    const { state, compounds } = fixture();
+    expect(state.searchCompounds('').map((r) => r.id)).toEqual([
+      compounds.ethanol.id,
+      compounds.aspirin.id,
+      compounds.caffeine.id,
+      compounds.draft.id, // never submitted
+    ]);
+  });
+
+  test('returns no results when nothing matches', () => {
+    const { state } = fixture();
+    expect(state.searchCompounds('ibuprofen')).toEqual([]);
+  });
+
+  test('limits results to a campaign', () => {
+    const { state, kinase, compounds } = fixture();
+    const ids = state.searchCompounds('', { campaign_id: kinase.id }).map((r) => r.id);
+    expect(ids).toEqual([compounds.ethanol.id, compounds.aspirin.id, compounds.draft.id]);
+  });
+
+  test('limits results to a run', () => {
+    const { state, screen, compounds } = fixture();
+    const ids = state.searchCompounds('aspirin', { run_id: screen.id }).map((r) => r.id);
+    expect(ids).toEqual([compounds.aspirin.id]);
+  });
+
+  test('a run outside the campaign scope matches nothing', () => {
+    const { state, screen } = fixture();
+    const other = state.data.campaigns[1];
+    expect(state.searchCompounds('', { campaign_id: other.id, run_id: screen.id })).toEqual([]);
+  });
+
+  test('results carry their campaign and run for display', () => {
+    const { state, kinase, screen, compounds } = fixture();
+    const [result] = state.searchCompounds('ethanol');
+    expect(result).toMatchObject({
+      ...compounds.ethanol,
+      campaign_id: kinase.id,
+      campaign_name: 'Kinase',
+      run_id: screen.id,
+      run_name: 'Screen',
+    });
+  });
+});